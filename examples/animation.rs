@@ -0,0 +1,60 @@
+//! Spins a dot around the middle of the display, and sends it across the screen whenever button A is pressed.
+
+#![no_std]
+#![no_main]
+#![feature(type_alias_impl_trait)]
+
+extern crate defmt_rtt;
+extern crate panic_probe;
+
+use embassy::executor::Spawner;
+use embassy::time::Duration;
+use embassy_microbit::display::Animation;
+use embassy_microbit::display::Frame;
+use embassy_microbit::display::Image;
+use embassy_nrf::Peripherals;
+use futures::future::select;
+use futures::pin_mut;
+
+const DELAY: Duration = Duration::from_millis(100);
+
+const fn dot(row: usize, col: usize) -> Frame {
+    let mut image = Image::BLANK;
+    image.0[row][col] = 255;
+    Frame::new(image, DELAY)
+}
+
+static SPIN: [Frame; 8] = [
+    dot(1, 1),
+    dot(1, 2),
+    dot(1, 3),
+    dot(2, 3),
+    dot(3, 3),
+    dot(3, 2),
+    dot(3, 1),
+    dot(2, 1),
+];
+
+static SWIPE: [Frame; 5] = [dot(2, 0), dot(2, 1), dot(2, 2), dot(2, 3), dot(2, 4)];
+
+#[embassy::main]
+async fn main(spawner: Spawner, peripherals: Peripherals) {
+    let mut display = embassy_microbit::display!(peripherals);
+    let mut button_a = embassy_microbit::button_a!(peripherals, &spawner);
+
+    let spin = Animation::new(&SPIN).looping();
+    let swipe = Animation::new(&SWIPE);
+
+    loop {
+        {
+            // This never finishes, but it would keep spinning in the background even after we stop waiting for it.
+            let spin = display.play(&spin);
+            let press = button_a.wait_for_press();
+            pin_mut!(spin, press);
+            select(spin, press).await;
+        }
+
+        // Playing another animation stops the spinning one.
+        display.play(&swipe).await;
+    }
+}
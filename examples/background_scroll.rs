@@ -0,0 +1,26 @@
+//! Keeps scrolling some text until button A is pressed, without blocking while it scrolls.
+
+#![no_std]
+#![no_main]
+#![feature(type_alias_impl_trait)]
+
+extern crate defmt_rtt;
+extern crate panic_probe;
+
+use embassy::executor::Spawner;
+use embassy_microbit::display::Image;
+use embassy_nrf::Peripherals;
+
+#[embassy::main]
+async fn main(spawner: Spawner, peripherals: Peripherals) {
+    let mut display = embassy_microbit::display!(peripherals);
+    let mut button_a = embassy_microbit::button_a!(peripherals, &spawner);
+
+    display.start_scroll("Press A to stop ", true);
+
+    // The display keeps scrolling by itself while we wait.
+    button_a.wait_for_press().await;
+
+    display.stop();
+    display.show(Image::from('A'));
+}
@@ -0,0 +1,120 @@
+//! Measures how many CPU cycles `Display::show` takes, which is mostly spent working out the steps for the new image.
+//!
+//! It also times a copy of the old way of working out steps against the crate's current one, for a before and after comparison.
+
+#![no_std]
+#![no_main]
+#![feature(type_alias_impl_trait)]
+#![feature(bench_black_box)]
+
+extern crate defmt_rtt;
+extern crate panic_probe;
+
+use core::hint::black_box;
+use core::mem;
+
+use cortex_m::peripheral::syst::SystClkSource;
+use cortex_m::peripheral::SYST;
+use embassy::executor::Spawner;
+use embassy_microbit::display::matrix::level_ticks;
+use embassy_microbit::display::matrix::HW_COLS;
+use embassy_microbit::display::matrix::HW_ROWS;
+use embassy_microbit::display::Image;
+use embassy_microbit::display::GAMMA;
+use embassy_nrf::Peripherals;
+
+const RUNS: u32 = 100;
+/// Roughly how long each row is lit for at the default refresh rate.
+const TICKS_PER_ROW: u16 = 5555;
+
+/// Works out the steps for each hardware row the way `Display::show` used to: a division per LED, followed by sorting each row.
+///
+/// This is laid out the same way as `Image::steps`, so it's a like-for-like comparison on both the v1 and the v2.
+fn old_steps(
+    image: &Image,
+    curve: &[u8; 256],
+    brightness: u8,
+) -> [[(u16, usize); HW_COLS]; HW_ROWS] {
+    let mut out = [[(0, 0); HW_COLS]; HW_ROWS];
+    for (row, hw_row) in out.iter_mut().zip(image.hw_rows().iter()) {
+        for (j, col) in row.iter_mut().enumerate() {
+            let value = curve[hw_row[j] as usize] as u32 * brightness as u32;
+            *col = ((TICKS_PER_ROW as u32 * value / (255 * 255)) as u16, j);
+        }
+        row.sort_unstable_by_key(|&(time, _)| time);
+    }
+    out
+}
+
+/// Returns the average number of cycles `f` takes over `RUNS` runs.
+fn measure(mut f: impl FnMut()) -> u32 {
+    // SysTick counts down.
+    let start = SYST::get_current();
+    for _ in 0..RUNS {
+        f();
+    }
+    let cycles = start.wrapping_sub(SYST::get_current()) & 0x00ff_ffff;
+    cycles / RUNS
+}
+
+#[embassy::main]
+async fn main(_spawner: Spawner, peripherals: Peripherals) {
+    let mut display = embassy_microbit::display!(peripherals);
+
+    // The Cortex-M0 in the v1 doesn't have a cycle counter, so use SysTick instead.
+    let mut syst = cortex_m::Peripherals::take().unwrap().SYST;
+    syst.set_clock_source(SystClkSource::Core);
+    syst.set_reload(0x00ff_ffff);
+    syst.clear_current();
+    syst.enable_counter();
+
+    let images = [
+        Image::HEART,
+        Image::HAPPY,
+        // Every LED at a different level, to get the worst case for sorting.
+        Image([
+            [250, 10, 240, 20, 230],
+            [30, 220, 40, 210, 50],
+            [200, 60, 190, 70, 180],
+            [80, 170, 90, 160, 100],
+            [150, 110, 140, 120, 130],
+        ]),
+    ];
+
+    let table = level_ticks(&GAMMA, 255, TICKS_PER_ROW);
+    defmt::info!(
+        "building the on-time table: {} cycles, once per brightness change",
+        measure(|| {
+            black_box(level_ticks(
+                black_box(&GAMMA),
+                black_box(255),
+                black_box(TICKS_PER_ROW),
+            ));
+        })
+    );
+
+    for image in images {
+        let show = measure(|| display.show(image.clone()));
+        let old = measure(|| {
+            black_box(old_steps(
+                black_box(&image),
+                black_box(&GAMMA),
+                black_box(255),
+            ));
+        });
+        let new = measure(|| {
+            black_box(black_box(&image).steps(black_box(&table)));
+        });
+
+        defmt::info!(
+            "{}: {} cycles per show; working out steps took {} cycles before and {} after",
+            image,
+            show,
+            old,
+            new
+        );
+    }
+
+    // Dropping the `Display` will cancel rendering.
+    mem::forget(display);
+}
@@ -0,0 +1,37 @@
+//! Draws a small map bigger than the display, and pans around it.
+
+#![no_std]
+#![no_main]
+#![feature(type_alias_impl_trait)]
+
+extern crate defmt_rtt;
+extern crate panic_probe;
+
+use embassy::executor::Spawner;
+use embassy::time::Duration;
+use embassy::time::Timer;
+use embassy_microbit::display::Canvas;
+use embassy_nrf::Peripherals;
+
+#[embassy::main]
+async fn main(_spawner: Spawner, peripherals: Peripherals) {
+    let mut display = embassy_microbit::display!(peripherals);
+
+    let mut canvas = Canvas::<20, 10>::BLANK;
+    canvas.draw_rect(0, 0, 20, 10, 64);
+    canvas.draw_line(0, 0, 19, 9, 128);
+    let end = canvas.draw_text("Hi", 3, 3);
+    canvas.fill_rect(end, 4, 2, 2, 255);
+
+    loop {
+        // Pan across the top, then back along the bottom.
+        for x in 0..=15 {
+            display.show(canvas.view(x, 0));
+            Timer::after(Duration::from_millis(100)).await;
+        }
+        for x in (0..=15).rev() {
+            display.show(canvas.view(x, 5));
+            Timer::after(Duration::from_millis(100)).await;
+        }
+    }
+}
@@ -6,10 +6,11 @@ extern crate defmt_rtt;
 extern crate panic_probe;
 
 use embassy::executor::Spawner;
-use embassy::time::Duration;
-use embassy::time::Timer;
 use embassy_microbit::display::Image;
 use embassy_nrf::Peripherals;
+use futures::future::select;
+use futures::future::Either;
+use futures::pin_mut;
 
 #[embassy::main]
 async fn main(spawner: Spawner, peripherals: Peripherals) {
@@ -20,27 +21,31 @@ async fn main(spawner: Spawner, peripherals: Peripherals) {
     let mut pixel = 0;
 
     loop {
-        if button_a.was_pressed() {
-            if pixel == 0 {
-                pixel = 24;
-            } else {
-                pixel -= 1;
-            }
-        }
-
-        if button_b.was_pressed() {
-            pixel += 1;
-            if pixel == 25 {
-                pixel = 0;
-            }
-        }
-
         let mut image = Image::BLANK;
 
         image[pixel / 5][pixel % 5] = 255;
 
         display.show(image);
 
-        Timer::after(Duration::from_millis(10)).await;
+        let a = button_a.wait_for_press();
+        let b = button_b.wait_for_press();
+        pin_mut!(a);
+        pin_mut!(b);
+
+        match select(a, b).await {
+            Either::Left(_) => {
+                if pixel == 0 {
+                    pixel = 24;
+                } else {
+                    pixel -= 1;
+                }
+            }
+            Either::Right(_) => {
+                pixel += 1;
+                if pixel == 25 {
+                    pixel = 0;
+                }
+            }
+        }
     }
 }
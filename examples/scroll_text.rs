@@ -13,4 +13,7 @@ async fn main(_spawner: Spawner, peripherals: Peripherals) {
     let mut display = embassy_microbit::display!(peripherals);
 
     display.scroll("Hello, World!").await;
+
+    let answer = 6 * 7;
+    display.scroll_fmt(format_args!("6 x 7 = {}", answer)).await;
 }
@@ -18,23 +18,21 @@ async fn main(spawner: Spawner, peripherals: Peripherals) {
     let button_b = embassy_microbit::button_b!(peripherals, &spawner);
 
     loop {
-        let mut image = Image::BLANK;
-
-        if button_a.is_pressed() {
-            image[1][1] = 255;
-        }
-
-        if button_b.is_pressed() {
-            image[1][3] = 255;
-        }
-
-        if button_a.is_pressed() && button_b.is_pressed() {
-            image[3][0] = 255;
-            image[3][4] = 255;
-            image[4][1] = 255;
-            image[4][2] = 255;
-            image[4][3] = 255;
-        }
+        let image = if button_a.is_pressed() && button_b.is_pressed() {
+            Image::HAPPY
+        } else {
+            let mut image = Image::BLANK;
+
+            if button_a.is_pressed() {
+                image[1][1] = 255;
+            }
+
+            if button_b.is_pressed() {
+                image[1][3] = 255;
+            }
+
+            image
+        };
 
         display.show(image);
 
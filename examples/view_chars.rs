@@ -6,10 +6,11 @@ extern crate defmt_rtt;
 extern crate panic_probe;
 
 use embassy::executor::Spawner;
-use embassy::time::Duration;
-use embassy::time::Timer;
 use embassy_microbit::display::Image;
 use embassy_nrf::Peripherals;
+use futures::future::select;
+use futures::future::Either;
+use futures::pin_mut;
 
 #[embassy::main]
 async fn main(spawner: Spawner, peripherals: Peripherals) {
@@ -20,15 +21,16 @@ async fn main(spawner: Spawner, peripherals: Peripherals) {
     let mut char = b'a';
 
     loop {
-        if button_a.was_pressed() {
-            char = char.wrapping_sub(1);
-        }
-        if button_b.was_pressed() {
-            char = char.wrapping_add(1);
-        }
-
         display.show(Image::from(char::from(char)));
 
-        Timer::after(Duration::from_millis(10)).await;
+        let a = button_a.wait_for_press();
+        let b = button_b.wait_for_press();
+        pin_mut!(a);
+        pin_mut!(b);
+
+        match select(a, b).await {
+            Either::Left(_) => char = char.wrapping_sub(1),
+            Either::Right(_) => char = char.wrapping_add(1),
+        }
     }
 }
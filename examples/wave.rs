@@ -6,19 +6,15 @@ extern crate defmt_rtt;
 extern crate panic_probe;
 
 use embassy::executor::Spawner;
-use embassy::time::{Duration, Instant, Timer};
+use embassy::time::Instant;
 use embassy_microbit::display::Image;
 use embassy_nrf::Peripherals;
 
-const FRAME_RATE: u64 = 60;
-const FRAME_PERIOD: Duration = Duration::from_micros(1_000_000 / FRAME_RATE);
-
 #[embassy::main]
 async fn main(_spawner: Spawner, peripherals: Peripherals) {
     let mut display = embassy_microbit::display!(peripherals);
 
     let start = Instant::now();
-    let mut next_frame = start + FRAME_PERIOD;
     loop {
         let elapsed = start.elapsed().as_millis() as f64 / 75.0;
         let mut image = [[0; 5]; 5];
@@ -41,10 +37,7 @@ async fn main(_spawner: Spawner, peripherals: Peripherals) {
             }
         }
 
-        display.show(Image(image));
-
-        Timer::at(next_frame).await;
-
-        next_frame += FRAME_PERIOD;
+        // Render a new image for every frame the display draws.
+        display.show_and_wait_vsync(Image(image)).await;
     }
 }
@@ -1,146 +1,692 @@
+use core::cell::Cell;
+use core::cell::RefCell;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::ops::DerefMut;
 use core::sync::atomic::Ordering;
+use core::task::Context;
+use core::task::Poll;
+use core::task::Waker;
 
+use atomic_polyfill::AtomicBool;
 use atomic_polyfill::AtomicU32;
+use critical_section::CriticalSection;
 use embassy::blocking_mutex::CriticalSectionMutex;
 use embassy::executor::Spawner;
 use embassy::task;
 use embassy::time::Duration;
+use embassy::time::Instant;
 use embassy::time::Timer;
+use embassy::traits::gpio::WaitForHigh;
+use embassy::traits::gpio::WaitForLow;
+use embassy::util::Steal;
+use embassy::waitqueue::AtomicWaker;
 use embassy_nrf::gpio;
+use embassy_nrf::gpio::AnyPin;
 use embassy_nrf::gpio::Pin;
 use embassy_nrf::gpio::Pull;
+use embassy_nrf::gpiote::PortInput;
 use embedded_hal::digital::v2::InputPin;
-use once_cell::unsync::OnceCell;
+use futures::future::poll_fn;
+use futures::future::select;
+use futures::future::Either;
+use futures::pin_mut;
 
+use crate::debounce::Debouncer;
+use crate::debounce::Transition;
+use crate::gesture::Chords;
+use crate::gesture::EventLog;
+use crate::gesture::Gestures;
+use crate::pins::gpio_port;
 use crate::pins::BtnA;
 use crate::pins::BtnB;
 
-static A_INPUT: CriticalSectionMutex<OnceCell<gpio::Input<'static, BtnA>>> =
-    CriticalSectionMutex::new(OnceCell::new());
-static B_INPUT: CriticalSectionMutex<OnceCell<gpio::Input<'static, BtnB>>> =
-    CriticalSectionMutex::new(OnceCell::new());
+pub use crate::gesture::ButtonConfig;
+pub use crate::gesture::ButtonEvent;
+pub use crate::gesture::ButtonId;
 
-static A_PRESS_COUNT: AtomicU32 = AtomicU32::new(0);
-static B_PRESS_COUNT: AtomicU32 = AtomicU32::new(0);
+/// The value of `ButtonState::psel` for inputs which don't have a pin that can be read directly.
+const NO_PIN: u32 = u32::MAX;
 
-#[task]
-async fn poll_buttons() {
-    // This algorithm was taken from the official micro:bit runtime (https://github.com/lancaster-university/codal-core/blob/master/source/drivers/Button.cpp)
-    const MIN_SIGMA: u8 = 0;
-    const MAX_SIGMA: u8 = 12;
+/// The maximum number of tasks which can wait on the same button at once without having to take turns.
+const MAX_WAKERS: usize = 4;
 
-    const SIGMA_HIGH_THRESHOLD: u8 = 8;
-    const SIGMA_LOW_THRESHOLD: u8 = 2;
-
-    let mut a_sigma: u8 = 0;
-    let mut a_pressed = false;
+/// Like `AtomicWaker`, except that it can hold several wakers so that several tasks can wait on the same button.
+struct WakerSet {
+    wakers: CriticalSectionMutex<RefCell<[Option<Waker>; MAX_WAKERS]>>,
+}
 
-    let mut b_sigma: u8 = 0;
-    let mut b_pressed = false;
+impl WakerSet {
+    const fn new() -> Self {
+        Self {
+            wakers: CriticalSectionMutex::new(RefCell::new([None, None, None, None])),
+        }
+    }
 
-    loop {
+    fn register(&self, waker: &Waker) {
         critical_section::with(|cs| {
-            if let Some(pin) = A_INPUT.borrow(cs).get() {
-                if pin.is_low().unwrap() {
-                    if a_sigma < MAX_SIGMA {
-                        a_sigma += 1;
+            let mut wakers = self.wakers.borrow(cs).borrow_mut();
+
+            if wakers.iter().flatten().any(|other| other.will_wake(waker)) {
+                return;
+            }
+
+            if let Some(slot) = wakers.iter_mut().find(|slot| slot.is_none()) {
+                *slot = Some(waker.clone());
+            } else {
+                // There's no room left, so wake everyone else up to make them re-register.
+                // They'll end up taking turns, but that's better than any of them never being woken.
+                for other in wakers.iter_mut() {
+                    if let Some(other) = other.take() {
+                        other.wake();
                     }
-                } else if a_sigma > MIN_SIGMA {
-                    a_sigma -= 1;
                 }
+                wakers[0] = Some(waker.clone());
+            }
+        })
+    }
 
-                if a_sigma > SIGMA_HIGH_THRESHOLD && !a_pressed {
-                    A_PRESS_COUNT.fetch_add(1, Ordering::Relaxed);
-                    a_pressed = true;
-                } else if a_sigma < SIGMA_LOW_THRESHOLD && a_pressed {
-                    a_pressed = false;
+    fn wake(&self) {
+        critical_section::with(|cs| {
+            for waker in self.wakers.borrow(cs).borrow_mut().iter_mut() {
+                if let Some(waker) = waker.take() {
+                    waker.wake();
                 }
             }
+        })
+    }
+}
+
+/// Everything shared between a button's `Button` handle and its debouncing task.
+pub(crate) struct ButtonState {
+    /// Whether this state is in use by an input.
+    claimed: AtomicBool,
+    /// Incremented whenever the input using this state is freed, so that stale subscribers can tell.
+    generation: AtomicU32,
+    /// Set by `Button::free` to tell the debouncing task to stop.
+    stopping: AtomicBool,
+    /// Woken when `stopping` is set.
+    stop_waker: AtomicWaker,
+    /// Set to ask a touch input's task to recalibrate itself.
+    pub(crate) recalibrate: AtomicBool,
+    config: CriticalSectionMutex<Cell<ButtonConfig>>,
+    events: CriticalSectionMutex<RefCell<EventLog>>,
+    /// The `psel_bits` of the input's pin, so that `Subscriber::is_pressed` can read it directly, or `NO_PIN` if it doesn't have one.
+    psel: AtomicU32,
+    /// Whether the input is pressed while its pin is low.
+    active_low: AtomicBool,
+    /// The debounced state of the button.
+    pressed: AtomicBool,
+    press_count: AtomicU32,
+    release_count: AtomicU32,
+    /// Woken whenever the button is pressed or released, emits an event, or is freed.
+    wakers: WakerSet,
+}
+
+impl ButtonState {
+    const fn new() -> Self {
+        Self {
+            claimed: AtomicBool::new(false),
+            generation: AtomicU32::new(0),
+            stopping: AtomicBool::new(false),
+            stop_waker: AtomicWaker::new(),
+            recalibrate: AtomicBool::new(false),
+            config: CriticalSectionMutex::new(Cell::new(ButtonConfig::DEFAULT)),
+            events: CriticalSectionMutex::new(RefCell::new(EventLog::new())),
+            psel: AtomicU32::new(NO_PIN),
+            active_low: AtomicBool::new(false),
+            pressed: AtomicBool::new(false),
+            press_count: AtomicU32::new(0),
+            release_count: AtomicU32::new(0),
+            wakers: WakerSet::new(),
+        }
+    }
+
+    /// Claims an unused state from `INPUTS`, if there are any left.
+    pub(crate) fn claim() -> Option<&'static ButtonState> {
+        INPUTS.iter().find(|state| {
+            state
+                .claimed
+                .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        })
+    }
+
+    /// Feeds a new sample of the button into `debouncer` and `gestures`, and emits any resulting events.
+    pub(crate) fn update(
+        &self,
+        cs: CriticalSection,
+        active: bool,
+        debouncer: &mut Debouncer,
+        gestures: &mut Gestures,
+        now: Instant,
+    ) {
+        let transition = debouncer.update(active);
+
+        let config = self.config.borrow(cs).get();
+        let mut events = self.events.borrow(cs).borrow_mut();
+        let event_count = events.count;
+        let mut emit = |event: ButtonEvent| events.push(event, now);
+
+        match transition {
+            Some(Transition::Pressed) => {
+                self.press_count.fetch_add(1, Ordering::Relaxed);
+                self.pressed.store(true, Ordering::Relaxed);
+                gestures.press(now, &config, &mut emit);
+            }
+            Some(Transition::Released) => {
+                self.release_count.fetch_add(1, Ordering::Relaxed);
+                self.pressed.store(false, Ordering::Relaxed);
+                gestures.release(now, &config, &mut emit);
+            }
+            None => {}
+        }
+
+        gestures.tick(now, &config, &mut emit);
+
+        // Presses and releases always emit events, so this covers them too.
+        if events.count != event_count {
+            self.wakers.wake();
+        }
+    }
+
+    /// Waits until `Button::free` asks the debouncing task to stop.
+    pub(crate) async fn wait_for_stop(&self) {
+        poll_fn(|cx| {
+            self.stop_waker.register(cx.waker());
+
+            if self.stopping.load(Ordering::Relaxed) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Returns this state to the pool once its debouncing task has stopped, and lets `Button::free` know.
+    pub(crate) fn release(&self) {
+        critical_section::with(|cs| self.config.borrow(cs).set(ButtonConfig::DEFAULT));
+        self.psel.store(NO_PIN, Ordering::Relaxed);
+        self.pressed.store(false, Ordering::Relaxed);
+        self.stopping.store(false, Ordering::Relaxed);
+        self.recalibrate.store(false, Ordering::Relaxed);
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        self.claimed.store(false, Ordering::Relaxed);
+        self.wakers.wake();
+    }
+
+    /// Asks the task using this state to stop, and waits until it has.
+    pub(crate) async fn stop(&self, generation: u32) {
+        self.stopping.store(true, Ordering::Relaxed);
+        self.stop_waker.wake();
+
+        poll_fn(|cx| {
+            self.wakers.register(cx.waker());
+
+            if self.generation.load(Ordering::Relaxed) != generation {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+// This is needed to be able to use `ButtonState::new()` in an array initializer.
+#[allow(clippy::declare_interior_mutable_const)]
+const UNCLAIMED: ButtonState = ButtonState::new();
+static INPUTS: [ButtonState; MAX_INPUTS] = [UNCLAIMED; MAX_INPUTS];
+
+/// Waits for `pin` to be at the given level.
+async fn wait_for_level(pin: &mut PortInput<'static, AnyPin>, low: bool) {
+    if low {
+        pin.wait_for_low().await
+    } else {
+        pin.wait_for_high().await
+    }
+}
+
+/// Defines `MAX_INPUTS`, along with `watch_input` with a task pool of that size.
+///
+/// `#[task]` only takes a literal pool size, so this is what stops the two from drifting apart.
+macro_rules! with_max_inputs {
+    ($max_inputs:tt, $watch_input:item) => {
+        /// The maximum number of debounced inputs (including the on-board buttons and touch inputs) which can exist at once.
+        ///
+        /// This is enough for both buttons, the logo, and edge connector pins 0-2 and 8-16, with one to spare.
+        pub const MAX_INPUTS: usize = $max_inputs;
+
+        #[task(pool_size = $max_inputs)]
+        $watch_input
+    };
+}
 
-            if let Some(pin) = B_INPUT.borrow(cs).get() {
-                if pin.is_low().unwrap() {
-                    if b_sigma < MAX_SIGMA {
-                        b_sigma += 1;
+with_max_inputs!(
+    16,
+    /// Debounces an input.
+    ///
+    /// Rather than sampling the pin forever, this only samples it every millisecond while it's bouncing,
+    /// and otherwise sleeps until GPIOTE reports that the pin has changed (or a `Hold` event is due).
+    async fn watch_input(
+        mut pin: PortInput<'static, AnyPin>,
+        active_low: bool,
+        state: &'static ButtonState,
+    ) {
+        let mut debouncer = Debouncer::new();
+        let mut gestures = Gestures::default();
+
+        loop {
+            let active = pin.is_low().unwrap() == active_low;
+            let now = Instant::now();
+
+            critical_section::with(|cs| {
+                state.update(cs, active, &mut debouncer, &mut gestures, now)
+            });
+
+            let wait = async {
+                if !debouncer.is_settled() {
+                    Timer::after(Duration::from_millis(1)).await;
+                } else if !debouncer.is_pressed() {
+                    // The input has settled in the released state; nothing's going to happen until it's pressed.
+                    wait_for_level(&mut pin, active_low).await;
+                } else {
+                    // The input has settled in the pressed state, so wait until it's released or a `Hold` event is due.
+                    let released = wait_for_level(&mut pin, !active_low);
+                    match gestures.next_deadline() {
+                        Some(deadline) => {
+                            let timer = Timer::at(deadline);
+                            pin_mut!(released);
+                            select(released, timer).await;
+                        }
+                        None => released.await,
                     }
-                } else if b_sigma > MIN_SIGMA {
-                    b_sigma -= 1;
                 }
+            };
+            let stop = state.wait_for_stop();
+            pin_mut!(wait);
+            pin_mut!(stop);
 
-                if b_sigma > SIGMA_HIGH_THRESHOLD && !b_pressed {
-                    B_PRESS_COUNT.fetch_add(1, Ordering::Relaxed);
-                    b_pressed = true;
-                } else if b_sigma < SIGMA_LOW_THRESHOLD && b_pressed {
-                    b_pressed = false;
-                }
+            if let Either::Right(_) = select(wait, stop).await {
+                break;
             }
-        });
+        }
 
-        Timer::after(Duration::from_millis(1)).await;
+        // Make sure the pin's been disconnected before `Button::free` hands it back out.
+        drop(pin);
+        state.release();
     }
-}
+);
+
+/// A debounced input on an arbitrary pin, such as an external push-button or reed switch.
+///
+/// This is exactly the same as a `Button`, just with a more fitting name.
+pub type DebouncedInput<T> = Button<T>;
 
+/// The owner of a debounced input.
+///
+/// All of the methods for reading the input are on `Subscriber`, which this derefs to.
+/// Dropping a `Button` leaves the input running for any subscribers; use `free` to stop it and get the pin back.
 pub struct Button<T: Pin> {
-    pin: &'static CriticalSectionMutex<OnceCell<gpio::Input<'static, T>>>,
-    press_count: &'static AtomicU32,
-    /// The value of `press_count` last time `was_pressed` was called.
-    last_press_count: u32,
+    subscriber: Subscriber,
+    _pin: PhantomData<T>,
 }
 
 impl Button<BtnA> {
     pub fn new_a(pin: BtnA, spawner: &Spawner) -> Button<BtnA> {
-        critical_section::with(|cs| {
-            A_INPUT
-                .borrow(cs)
-                .set(gpio::Input::new(pin, Pull::None)) // The buttons already have pullup resistors, so we don't need any.
-                .map_err(|_| ()) // gpio::Input doesn't impl `Debug`
-                .expect("Button A input already set")
-        });
+        // The buttons already have pullup resistors, so we don't need any.
+        Self::new(pin, Pull::None, true, spawner)
+    }
+}
+
+impl Button<BtnB> {
+    pub fn new_b(pin: BtnB, spawner: &Spawner) -> Button<BtnB> {
+        // The buttons already have pullup resistors, so we don't need any.
+        Self::new(pin, Pull::None, true, spawner)
+    }
+}
 
-        // Ignore errors, because it's perfectly fine for the other button to have already spawned the polling task
-        let _ = spawner.spawn(poll_buttons());
+impl<T: Pin> Button<T> {
+    /// Starts debouncing an input on `pin`, with the given pull resistor.
+    ///
+    /// If `active_low` is set, the input is considered pressed while the pin is low, and otherwise while it's high.
+    /// So a switch to ground needs `Pull::Up` and `active_low`, and a switch to 3V needs `Pull::Down` and not `active_low`;
+    /// if there's already an external resistor, use `Pull::None` with whichever one matches how the switch is wired.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are already `MAX_INPUTS` debounced inputs.
+    pub fn new(pin: T, pull: Pull, active_low: bool, spawner: &Spawner) -> Self {
+        let state = ButtonState::claim().expect("too many debounced inputs");
+        state.active_low.store(active_low, Ordering::Relaxed);
+        state.psel.store(pin.psel_bits(), Ordering::Relaxed);
+
+        let input = PortInput::new(gpio::Input::new(pin.degrade(), pull));
+        spawner
+            .spawn(watch_input(input, active_low, state))
+            .unwrap();
 
         Self {
-            pin: &A_INPUT,
-            press_count: &A_PRESS_COUNT,
-            last_press_count: 0,
+            subscriber: Subscriber::new(state),
+            _pin: PhantomData,
         }
     }
+
+    /// Returns a new handle for observing the input, which starts out having seen everything that's happened so far.
+    pub fn subscribe(&self) -> Subscriber {
+        Subscriber::new(self.subscriber.state)
+    }
+
+    /// Stops debouncing the input and returns its pin.
+    ///
+    /// Any remaining subscribers stop receiving anything; waiting on one of them will never complete.
+    pub async fn free(self) -> T
+    where
+        T: Steal,
+    {
+        self.subscriber.stop().await;
+
+        // Safety: the pin was moved into the debouncing task, which has now dropped it.
+        unsafe { T::steal() }
+    }
 }
 
-impl Button<BtnB> {
-    pub fn new_b(pin: BtnB, spawner: &Spawner) -> Button<BtnB> {
-        critical_section::with(|cs| {
-            B_INPUT
-                .borrow(cs)
-                .set(gpio::Input::new(pin, Pull::None)) // The buttons already have pullup resistors, so we don't need any.
-                .map_err(|_| ()) // gpio::Input doesn't impl `Debug`
-                .expect("Button B input already set")
-        });
+impl<T: Pin> Deref for Button<T> {
+    type Target = Subscriber;
+
+    fn deref(&self) -> &Self::Target {
+        &self.subscriber
+    }
+}
 
-        // Ignore errors, because it's perfectly fine for the other button to have already spawned the polling task
-        let _ = spawner.spawn(poll_buttons());
+impl<T: Pin> DerefMut for Button<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.subscriber
+    }
+}
+
+/// A handle for observing a debounced input, with its own position in the input's presses and events.
+///
+/// Cloning one gives a new handle starting from the same position.
+#[derive(Clone)]
+pub struct Subscriber {
+    state: &'static ButtonState,
+    /// The generation of `state` this was created for.
+    generation: u32,
+    /// The value of `press_count` last time `was_pressed` was called.
+    last_press_count: u32,
+    /// How many of the button's events have been returned by `next_event`.
+    last_event: u32,
+}
 
+impl Subscriber {
+    pub(crate) fn new(state: &'static ButtonState) -> Self {
         Self {
-            pin: &B_INPUT,
-            press_count: &B_PRESS_COUNT,
-            last_press_count: 0,
+            state,
+            generation: state.generation.load(Ordering::Relaxed),
+            last_press_count: state.press_count.load(Ordering::Relaxed),
+            last_event: critical_section::with(|cs| state.events.borrow(cs).borrow().count),
         }
     }
-}
 
-impl<T: Pin> Button<T> {
-    fn with_pin<R>(&self, f: impl FnOnce(&gpio::Input<'static, T>) -> R) -> R {
-        critical_section::with(|cs| f(self.pin.borrow(cs).get().unwrap()))
+    /// Returns whether the input this was subscribed to hasn't been freed.
+    fn is_current(&self) -> bool {
+        self.state.generation.load(Ordering::Relaxed) == self.generation
+    }
+
+    pub(crate) fn state(&self) -> &'static ButtonState {
+        self.state
     }
 
+    /// Stops the input this was subscribed to, and waits until it's stopped.
+    pub(crate) async fn stop(&self) {
+        self.state.stop(self.generation).await
+    }
+
+    /// Returns whether the button is currently held down.
+    ///
+    /// This reads the pin directly, so it isn't debounced; touch inputs don't have a level to read, so they're debounced anyway.
     pub fn is_pressed(&self) -> bool {
-        self.with_pin(|pin| pin.is_low().unwrap())
+        if !self.is_current() {
+            return false;
+        }
+
+        let psel = self.state.psel.load(Ordering::Relaxed);
+        if psel == NO_PIN {
+            return self.is_pressed_debounced();
+        }
+        let (port, pin) = gpio_port(psel);
+        let low = port.in_.read().bits() & (1 << pin) == 0;
+        low == self.state.active_low.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether the button is currently held down, according to the debouncer.
+    ///
+    /// This lags a few milliseconds behind `is_pressed`, but lines up with the presses and events reported.
+    pub fn is_pressed_debounced(&self) -> bool {
+        self.is_current() && self.state.pressed.load(Ordering::Relaxed)
     }
 
     pub fn was_pressed(&mut self) -> bool {
-        let press_count = self.press_count.load(Ordering::Relaxed);
-        let was_pressed = press_count > self.last_press_count;
+        if !self.is_current() {
+            return false;
+        }
+
+        let press_count = self.state.press_count.load(Ordering::Relaxed);
+        let was_pressed = press_count != self.last_press_count;
         self.last_press_count = press_count;
         was_pressed
     }
+
+    /// Waits until the button is next pressed.
+    ///
+    /// Presses which happened before this was called are ignored, and `was_pressed` won't report the press this returns for.
+    pub async fn wait_for_press(&mut self) {
+        let start = self.state.press_count.load(Ordering::Relaxed);
+        poll_fn(|cx| {
+            if !self.is_current() {
+                return Poll::Pending;
+            }
+            self.state.wakers.register(cx.waker());
+
+            let press_count = self.state.press_count.load(Ordering::Relaxed);
+            if press_count != start {
+                self.last_press_count = press_count;
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Waits until the button is next released.
+    ///
+    /// If the button isn't currently held down, this waits for it to be pressed and then released.
+    pub async fn wait_for_release(&mut self) {
+        let start = self.state.release_count.load(Ordering::Relaxed);
+        poll_fn(|cx| {
+            if !self.is_current() {
+                return Poll::Pending;
+            }
+            self.state.wakers.register(cx.waker());
+
+            if self.state.release_count.load(Ordering::Relaxed) != start {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Waits until the button is pressed and then released again.
+    ///
+    /// If the button is already held down when this is called, releasing it doesn't count; it has to be pressed again.
+    pub async fn wait_for_click(&mut self) {
+        // Take both counts at once, so that a press and release which both happen before this is next polled still count.
+        let (start_presses, start_releases, held) = critical_section::with(|_| {
+            (
+                self.state.press_count.load(Ordering::Relaxed),
+                self.state.release_count.load(Ordering::Relaxed),
+                self.state.pressed.load(Ordering::Relaxed),
+            )
+        });
+        // If the button's held, the next release just finishes that press, so it's the one after that which we want.
+        let releases_needed = 1 + held as u32;
+
+        poll_fn(|cx| {
+            if !self.is_current() {
+                return Poll::Pending;
+            }
+            self.state.wakers.register(cx.waker());
+
+            let press_count = self.state.press_count.load(Ordering::Relaxed);
+            let release_count = self.state.release_count.load(Ordering::Relaxed);
+            if press_count != start_presses
+                && release_count.wrapping_sub(start_releases) >= releases_needed
+            {
+                self.last_press_count = press_count;
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    pub fn config(&self) -> ButtonConfig {
+        critical_section::with(|cs| self.state.config.borrow(cs).get())
+    }
+
+    /// Changes the timings used to detect long clicks, double clicks and holds.
+    ///
+    /// This affects every subscriber to the input.
+    pub fn set_config(&self, config: ButtonConfig) {
+        if self.is_current() {
+            critical_section::with(|cs| self.state.config.borrow(cs).set(config))
+        }
+    }
+
+    /// Waits for the next event from the button.
+    ///
+    /// Events are buffered, so none are missed between calls unless more than a few build up.
+    pub async fn next_event(&mut self) -> ButtonEvent {
+        poll_fn(|cx| self.poll_next_event(cx)).await
+    }
+
+    fn poll_next_event(&mut self, cx: &mut Context<'_>) -> Poll<ButtonEvent> {
+        self.poll_peek_event(cx).map(|(event, _)| {
+            self.skip_event();
+            event
+        })
+    }
+
+    /// Like `poll_next_event`, except that the event isn't consumed, and when it happened is returned as well.
+    fn poll_peek_event(&mut self, cx: &mut Context<'_>) -> Poll<(ButtonEvent, Instant)> {
+        if !self.is_current() {
+            return Poll::Pending;
+        }
+        self.state.wakers.register(cx.waker());
+
+        match critical_section::with(|cs| {
+            self.state
+                .events
+                .borrow(cs)
+                .borrow()
+                .peek(&mut self.last_event)
+        }) {
+            Some(event) => Poll::Ready(event),
+            None => Poll::Pending,
+        }
+    }
+
+    /// Consumes the event last returned by `poll_peek_event`.
+    fn skip_event(&mut self) {
+        self.last_event = self.last_event.wrapping_add(1);
+    }
+}
+
+/// Both buttons together, which also reports when they're used as a chord.
+///
+/// Once both buttons are held down at the same time, an `Up` is reported for the one which was pressed first
+/// (since its `Down` has already been reported), followed by a `Down` for `ButtonId::AB`.
+/// The individual buttons' events are then suppressed until they're each pressed again after the chord has ended.
+/// Releasing one button and pressing it again while the other is still held just carries on the same chord.
+/// When the second button is released, `Up` and then `Click` or `LongClick` are reported for `ButtonId::AB`,
+/// depending on how long the chord lasted compared to `long_click` in button A's config.
+///
+/// A button which is already held down when this is created has its events suppressed until it's next pressed.
+pub struct Buttons {
+    a: Button<BtnA>,
+    b: Button<BtnB>,
+    chords: Chords,
+    /// An event which `chords` emitted along with the last one returned, which is returned next.
+    pending: Option<(ButtonId, ButtonEvent)>,
+}
+
+impl Buttons {
+    pub fn new(a: Button<BtnA>, b: Button<BtnB>) -> Self {
+        Self {
+            chords: Chords::new(a.is_pressed_debounced(), b.is_pressed_debounced()),
+            pending: None,
+            a,
+            b,
+        }
+    }
+
+    pub fn into_inner(self) -> (Button<BtnA>, Button<BtnB>) {
+        (self.a, self.b)
+    }
+
+    pub fn a(&mut self) -> &mut Button<BtnA> {
+        &mut self.a
+    }
+
+    pub fn b(&mut self) -> &mut Button<BtnB> {
+        &mut self.b
+    }
+
+    /// Waits for the next event from either button, or both of them together.
+    pub async fn next_event(&mut self) -> (ButtonId, ButtonEvent) {
+        poll_fn(|cx| loop {
+            if let Some(event) = self.pending.take() {
+                return Poll::Ready(event);
+            }
+
+            // Take whichever button's next event happened first, so that the chord logic sees them in the right order.
+            let (id, event, time) = match (self.a.poll_peek_event(cx), self.b.poll_peek_event(cx)) {
+                (Poll::Ready((a_event, a_time)), Poll::Ready((b_event, b_time))) => {
+                    if b_time < a_time {
+                        (ButtonId::B, b_event, b_time)
+                    } else {
+                        (ButtonId::A, a_event, a_time)
+                    }
+                }
+                (Poll::Ready((event, time)), Poll::Pending) => (ButtonId::A, event, time),
+                (Poll::Pending, Poll::Ready((event, time))) => (ButtonId::B, event, time),
+                (Poll::Pending, Poll::Pending) => return Poll::Pending,
+            };
+            match id {
+                ButtonId::A => self.a.skip_event(),
+                ButtonId::B => self.b.skip_event(),
+                ButtonId::AB => unreachable!(),
+            }
+
+            // `Chords` emits at most two events at once, so one slot is enough to hold the second until next time.
+            let mut first = None;
+            let pending = &mut self.pending;
+            let config = self.a.config();
+            self.chords
+                .process(id, event, time, &config, |event| match first {
+                    None => first = Some(event),
+                    Some(_) => *pending = Some(event),
+                });
+            if let Some(event) = first {
+                return Poll::Ready(event);
+            }
+        })
+        .await
+    }
 }
@@ -0,0 +1,209 @@
+//! A debouncer for noisy digital inputs, independent of any particular pin.
+
+use defmt::Format;
+
+/// A change in the debounced state of an input.
+#[derive(Clone, Copy, Debug, Format, PartialEq, Eq)]
+pub enum Transition {
+    Pressed,
+    Released,
+}
+
+/// A debouncing state machine, which is fed raw samples of an input and works out when it's really been pressed or released.
+///
+/// This algorithm was taken from the official micro:bit runtime (https://github.com/lancaster-university/codal-core/blob/master/source/drivers/Button.cpp).
+/// Each sample where the input is active increments `sigma` and each sample where it isn't decrements it, clamped between 0 and `max_sigma`.
+/// The input is considered pressed once `sigma` rises above `high_threshold`, and released once it falls below `low_threshold`.
+#[derive(Clone, Debug, Format, PartialEq, Eq)]
+pub struct Debouncer {
+    sigma: u8,
+    pressed: bool,
+    low_threshold: u8,
+    high_threshold: u8,
+    max_sigma: u8,
+}
+
+impl Debouncer {
+    pub const MIN_SIGMA: u8 = 0;
+    pub const MAX_SIGMA: u8 = 12;
+
+    pub const SIGMA_HIGH_THRESHOLD: u8 = 8;
+    pub const SIGMA_LOW_THRESHOLD: u8 = 2;
+
+    /// Creates a debouncer with the same thresholds as the official micro:bit runtime, starting out released.
+    pub const fn new() -> Self {
+        Self::with_thresholds(
+            Self::SIGMA_LOW_THRESHOLD,
+            Self::SIGMA_HIGH_THRESHOLD,
+            Self::MAX_SIGMA,
+        )
+    }
+
+    /// Creates a debouncer with custom thresholds, starting out released.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `low_threshold < high_threshold < max_sigma`.
+    pub const fn with_thresholds(low_threshold: u8, high_threshold: u8, max_sigma: u8) -> Self {
+        assert!(
+            low_threshold < high_threshold && high_threshold < max_sigma,
+            "debouncer thresholds must satisfy low < high < max"
+        );
+
+        Self {
+            sigma: Self::MIN_SIGMA,
+            pressed: false,
+            low_threshold,
+            high_threshold,
+            max_sigma,
+        }
+    }
+
+    /// Feeds in a new sample of the input, returning whether the debounced state changed.
+    pub fn update(&mut self, active: bool) -> Option<Transition> {
+        if active {
+            if self.sigma < self.max_sigma {
+                self.sigma += 1;
+            }
+        } else if self.sigma > Self::MIN_SIGMA {
+            self.sigma -= 1;
+        }
+
+        if self.sigma > self.high_threshold && !self.pressed {
+            self.pressed = true;
+            Some(Transition::Pressed)
+        } else if self.sigma < self.low_threshold && self.pressed {
+            self.pressed = false;
+            Some(Transition::Released)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the debounced state of the input.
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Returns whether `sigma` has saturated in the direction of the current state,
+    /// meaning the state can't change until a sample comes in which disagrees with it.
+    pub fn is_settled(&self) -> bool {
+        if self.pressed {
+            self.sigma == self.max_sigma
+        } else {
+            self.sigma == Self::MIN_SIGMA
+        }
+    }
+
+    pub fn sigma(&self) -> u8 {
+        self.sigma
+    }
+}
+
+impl Default for Debouncer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds each sample into `debouncer`, returning the transitions it reported.
+    fn feed(debouncer: &mut Debouncer, samples: &[bool]) -> Vec<Transition> {
+        samples
+            .iter()
+            .filter_map(|&active| debouncer.update(active))
+            .collect()
+    }
+
+    #[test]
+    fn bouncing_press() {
+        let mut debouncer = Debouncer::new();
+
+        // The contacts bounce a few times before settling, which shouldn't register as several presses.
+        let samples = [
+            true, false, true, true, false, true, false, true, true, true, true, true, true, true,
+            true, true, true,
+        ];
+        assert_eq!(feed(&mut debouncer, &samples), [Transition::Pressed]);
+        assert!(debouncer.is_pressed());
+
+        // The same goes for bouncing on release.
+        let samples = [
+            false, true, false, false, true, false, true, false, false, false, false, false, false,
+            false, false, false, false,
+        ];
+        assert_eq!(feed(&mut debouncer, &samples), [Transition::Released]);
+        assert!(!debouncer.is_pressed());
+    }
+
+    #[test]
+    fn press_threshold() {
+        let mut debouncer = Debouncer::new();
+
+        // `sigma` has to go above the threshold, not just reach it.
+        let samples = [true; Debouncer::SIGMA_HIGH_THRESHOLD as usize];
+        assert_eq!(feed(&mut debouncer, &samples), []);
+        assert_eq!(debouncer.update(true), Some(Transition::Pressed));
+    }
+
+    #[test]
+    fn release_glitch() {
+        let mut debouncer = Debouncer::new();
+        feed(&mut debouncer, &[true; Debouncer::MAX_SIGMA as usize]);
+        assert!(debouncer.is_pressed());
+
+        // Dropping out for a bit less than it takes to get below the low threshold is ignored.
+        let glitch = [false; (Debouncer::MAX_SIGMA - Debouncer::SIGMA_LOW_THRESHOLD) as usize];
+        assert_eq!(feed(&mut debouncer, &glitch), []);
+        assert!(debouncer.is_pressed());
+
+        // One more sample and it's released.
+        assert_eq!(debouncer.update(false), Some(Transition::Released));
+    }
+
+    #[test]
+    fn sigma_saturates() {
+        let mut debouncer = Debouncer::new();
+
+        feed(&mut debouncer, &[false; 20]);
+        assert_eq!(debouncer.sigma(), Debouncer::MIN_SIGMA);
+        assert!(debouncer.is_settled());
+
+        feed(&mut debouncer, &[true; 20]);
+        assert_eq!(debouncer.sigma(), Debouncer::MAX_SIGMA);
+        assert!(debouncer.is_settled());
+
+        // Holding it down for a long time doesn't make it take any longer to release.
+        let samples = [false; (Debouncer::MAX_SIGMA - Debouncer::SIGMA_LOW_THRESHOLD + 1) as usize];
+        assert_eq!(feed(&mut debouncer, &samples), [Transition::Released]);
+    }
+
+    #[test]
+    fn custom_thresholds() {
+        let mut debouncer = Debouncer::with_thresholds(1, 3, 5);
+
+        assert_eq!(feed(&mut debouncer, &[true; 3]), []);
+        assert_eq!(debouncer.update(true), Some(Transition::Pressed));
+
+        feed(&mut debouncer, &[true; 10]);
+        assert_eq!(debouncer.sigma(), 5);
+
+        assert_eq!(feed(&mut debouncer, &[false; 4]), []);
+        assert_eq!(debouncer.update(false), Some(Transition::Released));
+    }
+
+    #[test]
+    #[should_panic(expected = "debouncer thresholds must satisfy low < high < max")]
+    fn thresholds_out_of_order() {
+        Debouncer::with_thresholds(3, 3, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "debouncer thresholds must satisfy low < high < max")]
+    fn threshold_above_max() {
+        Debouncer::with_thresholds(1, 5, 5);
+    }
+}
@@ -0,0 +1,142 @@
+//! Playing back sequences of images on the display.
+
+use defmt::Format;
+use embassy::time::Duration;
+use embassy::time::Instant;
+
+use super::Image;
+
+/// A single image in an `Animation`, along with how long to show it for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub image: Image,
+    pub duration: Duration,
+}
+
+impl Frame {
+    pub const fn new(image: Image, duration: Duration) -> Self {
+        Self { image, duration }
+    }
+}
+
+/// What an `Animation` does once it reaches its last frame.
+#[derive(Clone, Copy, Debug, Format, PartialEq, Eq)]
+pub enum Repeat {
+    /// Stop, leaving the last frame on the display.
+    Once,
+    /// Start again from the first frame, forever.
+    Loop,
+    /// Play the frames backwards back to the start, and then forwards again, forever.
+    ///
+    /// The first and last frames aren't repeated when changing direction.
+    PingPong,
+}
+
+/// A sequence of frames to be played on the display with `Display::play`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Animation<'a> {
+    pub frames: &'a [Frame],
+    pub repeat: Repeat,
+}
+
+impl<'a> Animation<'a> {
+    /// Creates an animation which plays through `frames` once.
+    pub const fn new(frames: &'a [Frame]) -> Self {
+        Self {
+            frames,
+            repeat: Repeat::Once,
+        }
+    }
+
+    pub const fn looping(self) -> Self {
+        Self {
+            repeat: Repeat::Loop,
+            ..self
+        }
+    }
+
+    pub const fn ping_pong(self) -> Self {
+        Self {
+            repeat: Repeat::PingPong,
+            ..self
+        }
+    }
+}
+
+/// Keeps track of where we're up to in an animation.
+///
+/// This is advanced from the display's interrupt at the start of each frame, so animations keep playing in the background.
+pub(super) struct AnimationPlayer {
+    animation: Animation<'static>,
+    index: usize,
+    /// Whether a `PingPong` animation is currently going backwards.
+    reverse: bool,
+    /// When to move on from the current frame.
+    deadline: Instant,
+}
+
+impl AnimationPlayer {
+    /// Starts playing `animation`, returning the image to show first.
+    ///
+    /// Returns `None` if the animation is empty.
+    pub(super) fn start(animation: Animation<'static>, now: Instant) -> Option<(Self, Image)> {
+        let first = animation.frames.first()?;
+        let player = Self {
+            animation,
+            index: 0,
+            reverse: false,
+            deadline: now + first.duration,
+        };
+        Some((player, first.image.clone()))
+    }
+
+    /// Moves on to whichever frame should be showing at `now`.
+    ///
+    /// Returns the new image to show if it's changed, and whether the animation is still playing.
+    pub(super) fn advance(&mut self, now: Instant) -> (Option<Image>, bool) {
+        let mut changed = false;
+        let mut playing = true;
+        while now >= self.deadline {
+            match self.next_index() {
+                Some(index) => {
+                    self.index = index;
+                    // Make sure zero-length frames can't get us stuck here forever.
+                    self.deadline += self.animation.frames[index]
+                        .duration
+                        .max(Duration::from_ticks(1));
+                    changed = true;
+                }
+                None => {
+                    // The last frame stays on the display once its time is up.
+                    playing = false;
+                    break;
+                }
+            }
+        }
+
+        let image = changed.then(|| self.animation.frames[self.index].image.clone());
+        (image, playing)
+    }
+
+    fn next_index(&mut self) -> Option<usize> {
+        let len = self.animation.frames.len();
+        match self.animation.repeat {
+            Repeat::Once => Some(self.index + 1).filter(|&index| index < len),
+            Repeat::Loop => Some((self.index + 1) % len),
+            Repeat::PingPong if len == 1 => Some(0),
+            Repeat::PingPong => {
+                if self.reverse && self.index == 0 {
+                    self.reverse = false;
+                } else if !self.reverse && self.index == len - 1 {
+                    self.reverse = true;
+                }
+
+                if self.reverse {
+                    Some(self.index - 1)
+                } else {
+                    Some(self.index + 1)
+                }
+            }
+        }
+    }
+}
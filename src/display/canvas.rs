@@ -0,0 +1,547 @@
+//! Images bigger than the display, which can be shown a 5x5 window at a time.
+
+use core::ops::Deref;
+use core::ops::DerefMut;
+use core::ops::Range;
+
+use super::Image;
+
+/// A `W` x `H` grid of LED brightnesses, which can be drawn on and then shown on the display through a 5x5 window with `view`.
+///
+/// All the drawing methods take signed coordinates, and anything drawn off the edge of the canvas is left out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Canvas<const W: usize, const H: usize>(pub [[u8; W]; H]);
+
+impl<const W: usize, const H: usize> Deref for Canvas<W, H> {
+    type Target = [[u8; W]; H];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const W: usize, const H: usize> DerefMut for Canvas<W, H> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<const W: usize, const H: usize> Default for Canvas<W, H> {
+    fn default() -> Self {
+        Self::BLANK
+    }
+}
+
+impl<const W: usize, const H: usize> Canvas<W, H> {
+    pub const BLANK: Self = Self([[0; W]; H]);
+
+    pub const fn width(&self) -> usize {
+        W
+    }
+
+    pub const fn height(&self) -> usize {
+        H
+    }
+
+    /// Returns the brightness at `(x, y)`, or `None` if it's off the edge of the canvas.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<u8> {
+        self.get(y)?.get(x).copied()
+    }
+
+    pub fn set_pixel(&mut self, x: isize, y: isize, value: u8) {
+        if (0..W as isize).contains(&x) && (0..H as isize).contains(&y) {
+            self[y as usize][x as usize] = value;
+        }
+    }
+
+    /// Sets every LED to `value`, so `fill(0)` clears the canvas.
+    pub fn fill(&mut self, value: u8) {
+        for row in self.iter_mut() {
+            row.fill(value);
+        }
+    }
+
+    /// Draws a straight line from `(x0, y0)` to `(x1, y1)`, including both ends.
+    pub fn draw_line(&mut self, x0: isize, y0: isize, x1: isize, y1: isize, value: u8) {
+        let dx = (x1 as i128 - x0 as i128).unsigned_abs();
+        let dy = (y1 as i128 - y0 as i128).unsigned_abs();
+        if dx >= dy {
+            self.draw_line_along((x0, y0), (x1, y1), W, |canvas, x, y| {
+                canvas.set_pixel(x, y, value)
+            });
+        } else {
+            self.draw_line_along((y0, x0), (y1, x1), H, |canvas, y, x| {
+                canvas.set_pixel(x, y, value)
+            });
+        }
+    }
+
+    /// Draws a line from `start` to `end` using Bresenham's line algorithm, where each is a `(major, minor)` pair of coordinates
+    /// along the axis the line is longest in, and the one it isn't. `len` is the size of the canvas along the major axis.
+    ///
+    /// The line steps once along the major axis for every point, so only the points that are within `0..len` along it are drawn,
+    /// skipping straight to the first of them; this way drawing a line far longer than the canvas doesn't take forever.
+    fn draw_line_along(
+        &mut self,
+        (major0, minor0): (isize, isize),
+        (major1, minor1): (isize, isize),
+        len: usize,
+        mut set: impl FnMut(&mut Self, isize, isize),
+    ) {
+        // Work in `i128`s, which can hold the distance between any two `isize`s.
+        let (major0, minor0) = (major0 as i128, minor0 as i128);
+        let long = (major1 as i128 - major0).unsigned_abs();
+        let short = (minor1 as i128 - minor0).unsigned_abs();
+        let major_step = if major0 < major1 as i128 { 1 } else { -1 };
+        let minor_step = if minor0 < minor1 as i128 { 1 } else { -1 };
+
+        // The range of points which are within `0..len` along the major axis.
+        let (first, last) = if major_step == 1 {
+            (-major0, len as i128 - 1 - major0)
+        } else {
+            (major0 - (len as i128 - 1), major0)
+        };
+        let (first, last) = (first.max(0) as u128, last.min(long as i128));
+        if last < first as i128 {
+            return;
+        }
+
+        // After `t` steps along the major axis, Bresenham's algorithm has taken `(2 * short * t + long) / (2 * long)` steps
+        // along the minor axis (rounding down), so keep track of that and the remainder.
+        // They're worked out for the first point without multiplying by 2 first, since `short * t` can only just fit in a `u128`.
+        let product = short * first;
+        let (mut minor, mut remainder) = match product.checked_div(long) {
+            // The line is a single point.
+            None => (0, 0),
+            Some(quotient) => {
+                let remainder = product % long;
+                if 2 * remainder >= long {
+                    (quotient + 1, 2 * remainder - long)
+                } else {
+                    (quotient, 2 * remainder + long)
+                }
+            }
+        };
+
+        for t in first..=last as u128 {
+            // These can't overflow, since every point on the line is between its two ends.
+            set(
+                self,
+                (major0 + major_step * t as i128) as isize,
+                (minor0 + minor_step * minor as i128) as isize,
+            );
+
+            remainder += 2 * short;
+            if remainder >= 2 * long {
+                remainder -= 2 * long;
+                minor += 1;
+            }
+        }
+    }
+
+    /// Draws the outline of a `width` x `height` rectangle with its top-left corner at `(x, y)`.
+    pub fn draw_rect(&mut self, x: isize, y: isize, width: usize, height: usize, value: u8) {
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        // If the rectangle goes past the largest `isize`, its far edges are off the canvas anyway.
+        let (right, bottom) = (
+            x.saturating_add(to_isize(width - 1)),
+            y.saturating_add(to_isize(height - 1)),
+        );
+        self.draw_line(x, y, right, y, value);
+        self.draw_line(x, bottom, right, bottom, value);
+        self.draw_line(x, y, x, bottom, value);
+        self.draw_line(right, y, right, bottom, value);
+    }
+
+    /// Fills in a `width` x `height` rectangle with its top-left corner at `(x, y)`.
+    pub fn fill_rect(&mut self, x: isize, y: isize, width: usize, height: usize, value: u8) {
+        for row in &mut self[clip(y, height, H)] {
+            row[clip(x, width, W)].fill(value);
+        }
+    }
+
+    /// Copies `image` onto the canvas with its top-left corner at `(x, y)`.
+    pub fn draw_image(&mut self, image: &Image, x: isize, y: isize) {
+        for (j, row) in image.iter().enumerate() {
+            for (i, &value) in row.iter().enumerate() {
+                self.set_pixel(
+                    x.saturating_add(i as isize),
+                    y.saturating_add(j as isize),
+                    value,
+                );
+            }
+        }
+    }
+
+    /// Writes `text` onto the canvas using the display's font, starting with its top-left corner at `(x, y)`.
+    ///
+    /// The characters are kerned and spaced out the same way as when scrolling.
+    /// Returns the x coordinate just past the end of the text, so that more can be drawn after it.
+    pub fn draw_text(&mut self, text: &str, x: isize, y: isize) -> isize {
+        let mut x = x;
+        for char in text.chars() {
+            let glyph = Image::from(char);
+            let (start, end) = glyph.kerned_columns();
+            for col in start..end {
+                for (j, row) in glyph.iter().enumerate() {
+                    self.set_pixel(x, y.saturating_add(j as isize), row[col]);
+                }
+                x = x.saturating_add(1);
+            }
+
+            // Leave a column of space before the next character.
+            for j in 0..5 {
+                self.set_pixel(x, y.saturating_add(j), 0);
+            }
+            x = x.saturating_add(1);
+        }
+        x
+    }
+
+    /// Returns the 5x5 window onto the canvas with its top-left corner at `(x, y)`, for showing on the display.
+    ///
+    /// Any part of the window which is off the edge of the canvas is blank.
+    pub fn view(&self, x: isize, y: isize) -> Image {
+        let mut image = Image::BLANK;
+        for (j, row) in image.iter_mut().enumerate() {
+            for (i, value) in row.iter_mut().enumerate() {
+                let (x, y) = (x.saturating_add(i as isize), y.saturating_add(j as isize));
+                if (0..W as isize).contains(&x) && (0..H as isize).contains(&y) {
+                    *value = self[y as usize][x as usize];
+                }
+            }
+        }
+        image
+    }
+}
+
+/// Converts a size into an `isize`, clamping it to the largest one.
+fn to_isize(n: usize) -> isize {
+    isize::try_from(n).unwrap_or(isize::MAX)
+}
+
+/// Returns the part of the `len` cells starting from `start` which is within `0..size`.
+fn clip(start: isize, len: usize, size: usize) -> Range<usize> {
+    let end = start.saturating_add(to_isize(len));
+    let clamp = |n: isize| n.clamp(0, size as isize) as usize;
+    clamp(start)..clamp(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The points on the canvas which aren't blank, in row order.
+    fn lit<const W: usize, const H: usize>(canvas: &Canvas<W, H>) -> Vec<(usize, usize)> {
+        let mut points = Vec::new();
+        for (y, row) in canvas.iter().enumerate() {
+            for (x, &value) in row.iter().enumerate() {
+                if value != 0 {
+                    points.push((x, y));
+                }
+            }
+        }
+        points
+    }
+
+    /// The straightforward version of Bresenham's line algorithm, which walks the whole line.
+    fn reference_line<const W: usize, const H: usize>(
+        canvas: &mut Canvas<W, H>,
+        x0: isize,
+        y0: isize,
+        x1: isize,
+        y1: isize,
+    ) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let step_x = if x0 < x1 { 1 } else { -1 };
+        let step_y = if y0 < y1 { 1 } else { -1 };
+
+        let (mut x, mut y) = (x0, y0);
+        let mut error = dx + dy;
+        loop {
+            canvas.set_pixel(x, y, 255);
+            if x == x1 && y == y1 {
+                break;
+            }
+
+            let error2 = 2 * error;
+            if error2 >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if error2 <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    #[test]
+    fn line_octants() {
+        // A line 3 long and 1 across, reflected into each of the octants around (3, 3).
+        let shape = [(0, 0), (1, 0), (2, 1), (3, 1)];
+        for &(flip_x, flip_y, swap) in &[
+            (1, 1, false),
+            (1, 1, true),
+            (-1, 1, true),
+            (-1, 1, false),
+            (-1, -1, false),
+            (-1, -1, true),
+            (1, -1, true),
+            (1, -1, false),
+        ] {
+            let point = |(u, v): (isize, isize)| {
+                let (x, y) = if swap { (v, u) } else { (u, v) };
+                (3 + flip_x * x, 3 + flip_y * y)
+            };
+            let (x1, y1) = point(shape[3]);
+
+            let mut canvas = Canvas::<7, 7>::BLANK;
+            canvas.draw_line(3, 3, x1, y1, 255);
+            let mut expected: Vec<_> = shape
+                .iter()
+                .map(|&p| {
+                    let (x, y) = point(p);
+                    (x as usize, y as usize)
+                })
+                .collect();
+            expected.sort_by_key(|&(x, y)| (y, x));
+            assert_eq!(lit(&canvas), expected, "to ({}, {})", x1, y1);
+        }
+
+        // Horizontal, vertical and diagonal lines.
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_line(1, 2, 5, 2, 255);
+        assert_eq!(lit(&canvas), [(1, 2), (2, 2), (3, 2), (4, 2), (5, 2)]);
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_line(6, 4, 6, 1, 255);
+        assert_eq!(lit(&canvas), [(6, 1), (6, 2), (6, 3), (6, 4)]);
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_line(4, 0, 0, 4, 255);
+        assert_eq!(lit(&canvas), [(4, 0), (3, 1), (2, 2), (1, 3), (0, 4)]);
+    }
+
+    #[test]
+    fn line_single_point() {
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_line(2, 3, 2, 3, 255);
+        assert_eq!(lit(&canvas), [(2, 3)]);
+
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_line(-1, 3, -1, 3, 255);
+        canvas.draw_line(isize::MAX, isize::MIN, isize::MAX, isize::MIN, 255);
+        assert_eq!(lit(&canvas), []);
+    }
+
+    #[test]
+    fn line_matches_reference() {
+        // Every line between points in and around the canvas.
+        for x0 in -3..10 {
+            for y0 in -3..8 {
+                for x1 in -3..10 {
+                    for y1 in -3..8 {
+                        let mut canvas = Canvas::<7, 5>::BLANK;
+                        canvas.draw_line(x0, y0, x1, y1, 255);
+                        let mut expected = Canvas::<7, 5>::BLANK;
+                        reference_line(&mut expected, x0, y0, x1, y1);
+                        assert_eq!(canvas, expected, "({}, {}) to ({}, {})", x0, y0, x1, y1);
+                    }
+                }
+            }
+        }
+
+        // Long lines which only cross the canvas for a small part of their length.
+        for &(x0, y0, x1, y1) in &[
+            (-1000, -3, 1000, 7),
+            (1000, 7, -1000, -3),
+            (-997, 1001, 13, -9),
+            (2, -5000, 4, 3000),
+            (-12345, -6789, 54321, 9876),
+        ] {
+            let mut canvas = Canvas::<7, 5>::BLANK;
+            canvas.draw_line(x0, y0, x1, y1, 255);
+            let mut expected = Canvas::<7, 5>::BLANK;
+            reference_line(&mut expected, x0, y0, x1, y1);
+            assert_eq!(canvas, expected, "({}, {}) to ({}, {})", x0, y0, x1, y1);
+        }
+    }
+
+    #[test]
+    fn line_extremes() {
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_line(isize::MIN, isize::MIN, isize::MAX, isize::MAX, 255);
+        assert_eq!(lit(&canvas), [(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]);
+
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_line(isize::MAX, 1, isize::MIN, 1, 255);
+        canvas.draw_line(3, isize::MIN, 3, isize::MAX, 255);
+        assert_eq!(lit(&canvas)[..3], [(3, 0), (0, 1), (1, 1)]);
+        assert_eq!(lit(&canvas).len(), 7 + 5 - 1);
+
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_line(isize::MIN, isize::MAX, isize::MIN, isize::MIN, 255);
+        canvas.draw_line(isize::MIN, -1, isize::MAX, -1, 255);
+        assert_eq!(lit(&canvas), []);
+    }
+
+    #[test]
+    fn rects() {
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_rect(1, 1, 0, 3, 255);
+        canvas.draw_rect(1, 1, 3, 0, 255);
+        canvas.fill_rect(1, 1, 0, 3, 255);
+        canvas.fill_rect(1, 1, 3, 0, 255);
+        assert_eq!(lit(&canvas), []);
+
+        canvas.draw_rect(2, 3, 1, 1, 255);
+        assert_eq!(lit(&canvas), [(2, 3)]);
+
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_rect(1, 1, 3, 3, 255);
+        assert_eq!(
+            lit(&canvas),
+            [
+                (1, 1),
+                (2, 1),
+                (3, 1),
+                (1, 2),
+                (3, 2),
+                (1, 3),
+                (2, 3),
+                (3, 3)
+            ]
+        );
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.fill_rect(1, 1, 3, 2, 255);
+        assert_eq!(
+            lit(&canvas),
+            [(1, 1), (2, 1), (3, 1), (1, 2), (2, 2), (3, 2)]
+        );
+    }
+
+    #[test]
+    fn rects_off_edge() {
+        // Only the bottom-right corner of the outline is on the canvas.
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_rect(-2, -2, 4, 4, 255);
+        assert_eq!(lit(&canvas), [(1, 0), (0, 1), (1, 1)]);
+
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.fill_rect(5, 3, 4, 4, 255);
+        assert_eq!(lit(&canvas), [(5, 3), (6, 3), (5, 4), (6, 4)]);
+
+        // Huge rectangles, whose far edges are well past the canvas.
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_rect(0, 0, usize::MAX, usize::MAX, 255);
+        assert_eq!(lit(&canvas).len(), 7 + 5 - 1);
+        assert!(lit(&canvas).iter().all(|&(x, y)| x == 0 || y == 0));
+
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.draw_rect(isize::MIN, isize::MIN, usize::MAX, usize::MAX, 255);
+        canvas.draw_rect(isize::MAX, isize::MAX, usize::MAX, usize::MAX, 255);
+        canvas.fill_rect(isize::MIN, isize::MIN, usize::MAX, usize::MAX, 255);
+        canvas.fill_rect(isize::MAX, 0, usize::MAX, usize::MAX, 255);
+        canvas.fill_rect(7, 0, 1, 1, 255);
+        assert_eq!(lit(&canvas), []);
+
+        let mut canvas = Canvas::<7, 5>::BLANK;
+        canvas.fill_rect(-1, -1, usize::MAX, usize::MAX, 255);
+        assert_eq!(canvas, Canvas([[255; 7]; 5]));
+    }
+
+    #[test]
+    fn text() {
+        let text = "Hi!";
+        let widths: Vec<isize> = text
+            .chars()
+            .map(|char| {
+                let (start, end) = Image::from(char).kerned_columns();
+                (end - start) as isize
+            })
+            .collect();
+
+        let mut canvas = Canvas::<20, 5>::BLANK;
+        canvas.fill(255);
+        let end = canvas.draw_text(text, 1, 0);
+        assert_eq!(end, 1 + widths.iter().map(|width| width + 1).sum::<isize>());
+
+        // Each character is followed by a blank column, and nothing past the text is touched.
+        let mut x = 1;
+        for (char, &width) in text.chars().zip(&widths) {
+            let glyph = Image::from(char);
+            let (start, _) = glyph.kerned_columns();
+            for col in 0..width {
+                for y in 0..5 {
+                    assert_eq!(
+                        canvas[y][(x + col) as usize],
+                        glyph[y][start + col as usize]
+                    );
+                }
+            }
+            x += width;
+            assert!(
+                canvas.iter().all(|row| row[x as usize] == 0),
+                "after {:?}",
+                char
+            );
+            x += 1;
+        }
+        assert!(canvas
+            .iter()
+            .all(|row| row[x as usize..].iter().all(|&value| value == 255)));
+
+        // The end is still reported when the text's off the canvas.
+        let mut canvas = Canvas::<20, 5>::BLANK;
+        assert_eq!(canvas.draw_text(text, -100, 0), end - 101);
+        assert_eq!(canvas.draw_text(text, 0, 50), end - 1);
+        assert_eq!(canvas.draw_text("", 4, 0), 4);
+        assert_eq!(lit(&canvas), []);
+    }
+
+    #[test]
+    fn view() {
+        let mut canvas = Canvas::<7, 6>::BLANK;
+        for (y, row) in canvas.iter_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = (y * 7 + x + 1) as u8;
+            }
+        }
+
+        let image = canvas.view(1, 1);
+        for y in 0..5 {
+            assert_eq!(image[y], canvas[y + 1][1..6]);
+        }
+
+        // Partly off the top-left and the bottom-right.
+        let image = canvas.view(-2, -1);
+        for y in 0..5 {
+            for x in 0..5 {
+                let expected = if x >= 2 && y >= 1 {
+                    canvas[y - 1][x - 2]
+                } else {
+                    0
+                };
+                assert_eq!(image[y][x], expected, "at ({}, {})", x, y);
+            }
+        }
+        let image = canvas.view(4, 3);
+        for y in 0..5 {
+            for x in 0..5 {
+                let expected = if x < 3 && y < 3 {
+                    canvas[y + 3][x + 4]
+                } else {
+                    0
+                };
+                assert_eq!(image[y][x], expected, "at ({}, {})", x, y);
+            }
+        }
+
+        assert_eq!(canvas.view(7, 0), Image::BLANK);
+        assert_eq!(canvas.view(isize::MAX, isize::MAX), Image::BLANK);
+        assert_eq!(canvas.view(isize::MIN, 0), Image::BLANK);
+    }
+}
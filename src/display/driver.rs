@@ -0,0 +1,1074 @@
+//! Driving the LED matrix, and sensing light with it.
+
+use core::fmt;
+#[cfg(v2)]
+use core::sync::atomic::compiler_fence;
+#[cfg(v2)]
+use core::sync::atomic::Ordering;
+use core::task::Poll;
+
+use defmt::Format;
+use embassy::interrupt::Interrupt;
+use embassy::interrupt::InterruptExt;
+use embassy::time::Duration;
+use embassy::time::Instant;
+use embassy::time::Timer;
+use embassy::util::Forever;
+use embassy::waitqueue::WakerRegistration;
+use embassy_hal_common::peripheral::PeripheralMutex;
+use embassy_hal_common::peripheral::PeripheralState;
+use embassy_hal_common::peripheral::StateStorage;
+use embassy_nrf::gpio;
+use embassy_nrf::gpio::AnyPin;
+use embassy_nrf::gpio::Level;
+use embassy_nrf::gpio::OutputDrive;
+use embassy_nrf::gpio::Pin;
+use embassy_nrf::pac;
+#[cfg(not(v2))]
+use embassy_nrf::peripherals::ADC;
+#[cfg(v2)]
+use embassy_nrf::peripherals::SAADC;
+use embassy_nrf::peripherals::TIMER0;
+use embassy_nrf::peripherals::TIMER1;
+use embassy_nrf::peripherals::TIMER2;
+#[cfg(v2)]
+use embassy_nrf::peripherals::TIMER3;
+#[cfg(v2)]
+use embassy_nrf::peripherals::TIMER4;
+use embassy_nrf::timer;
+use embassy_nrf::timer::Timer as HwTimer;
+use embedded_hal::digital::v2::OutputPin;
+use futures::future::poll_fn;
+
+use crate::pins::gpio_port;
+use crate::pins::Col1;
+use crate::pins::Col2;
+use crate::pins::Col3;
+use crate::pins::Col4;
+use crate::pins::Col5;
+#[cfg(not(v2))]
+use crate::pins::Col6;
+#[cfg(not(v2))]
+use crate::pins::Col7;
+#[cfg(not(v2))]
+use crate::pins::Col8;
+#[cfg(not(v2))]
+use crate::pins::Col9;
+use crate::pins::Row1;
+use crate::pins::Row2;
+use crate::pins::Row3;
+#[cfg(v2)]
+use crate::pins::Row4;
+#[cfg(v2)]
+use crate::pins::Row5;
+
+use super::animation::AnimationPlayer;
+use super::matrix::level_ticks;
+use super::matrix::row_steps;
+use super::matrix::HW_COLS;
+use super::matrix::HW_ROWS;
+use super::scroll::FmtChars;
+use super::scroll::ScrollPlayer;
+use super::scroll::Scroller;
+use super::Animation;
+use super::Image;
+use super::ScrollOptions;
+use super::GAMMA;
+
+// The timer's frequency is 1MHz, so 1s is 1_000_000 ticks.
+const TICKS_PER_SECOND: u32 = 1_000_000;
+// Any less than this and there wouldn't be a distinct on-time for every LED value.
+const MIN_TICKS_PER_ROW: u32 = 255;
+
+// Light sensing takes up an extra row's worth of time at the end of a frame.
+// The LEDs are charged up in reverse for `LIGHT_CHARGE_TICKS`, and then the columns are left floating
+// while light discharges them, until the voltages of the analog-capable columns are read with the ADC
+// right at the end of the row. This is the same approach as the official micro:bit runtime.
+const LIGHT_CHARGE_TICKS: u16 = 100;
+/// How long to leave for each ADC conversion; a 10-bit conversion takes 68µs on the v1, and about 12µs on the v2.
+const LIGHT_CONVERSION_TICKS: u16 = 80;
+/// How long before each conversion to set up the ADC, so that it's ready to go without the interrupt having to wait for it.
+const LIGHT_PREPARE_TICKS: u16 = 5;
+/// The analog inputs which columns are connected to: columns 1-3 on the v1, and columns 1, 3 and 5 on the v2.
+#[cfg(not(v2))]
+const LIGHT_INPUTS: [u8; 3] = [5, 6, 7];
+#[cfg(v2)]
+const LIGHT_INPUTS: [u8; 3] = [4, 7, 6];
+/// The number of steps in the light-sensing row: letting the columns float, and then preparing and starting each conversion.
+const LIGHT_STEPS: usize = 1 + 2 * LIGHT_INPUTS.len();
+// The range of 10-bit ADC readings mapped onto light levels, taken from the official micro:bit runtime.
+// The brighter it is, the more the columns discharge, so a lower reading means more light.
+const LIGHT_MIN_READING: u32 = 75;
+const LIGHT_MAX_READING: u32 = 338;
+
+/// The ADC used to sense light, which has to be lent to `Display::read_light_level`.
+#[cfg(not(v2))]
+pub type LightSensorAdc = ADC;
+/// The ADC used to sense light, which has to be lent to `Display::read_light_level`.
+#[cfg(v2)]
+pub type LightSensorAdc = SAADC;
+
+pub struct Pins {
+    pub row1: Row1,
+    pub row2: Row2,
+    pub row3: Row3,
+    #[cfg(v2)]
+    pub row4: Row4,
+    #[cfg(v2)]
+    pub row5: Row5,
+    pub col1: Col1,
+    pub col2: Col2,
+    pub col3: Col3,
+    pub col4: Col4,
+    pub col5: Col5,
+    #[cfg(not(v2))]
+    pub col6: Col6,
+    #[cfg(not(v2))]
+    pub col7: Col7,
+    #[cfg(not(v2))]
+    pub col8: Col8,
+    #[cfg(not(v2))]
+    pub col9: Col9,
+}
+
+/// Settings for how the display is driven.
+#[derive(Clone, Copy, Debug, Format, PartialEq, Eq)]
+pub struct DisplayConfig {
+    /// How many times per second the whole display is redrawn, in Hz.
+    ///
+    /// Higher values flicker less on camera, at the cost of more interrupts.
+    pub refresh_rate: u32,
+}
+
+impl DisplayConfig {
+    pub const DEFAULT: Self = Self { refresh_rate: 60 };
+
+    /// Works out how long each row is lit for, in timer ticks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the refresh rate is too high for every LED value to be distinguishable,
+    /// or so low that a frame (plus the extra row used for sensing light) no longer fits in the 16-bit timer.
+    fn ticks_per_row(&self) -> u16 {
+        assert!(self.refresh_rate > 0, "refresh rate must be non-zero");
+        let ticks_per_row = TICKS_PER_SECOND / (self.refresh_rate * HW_ROWS as u32);
+        assert!(
+            ticks_per_row >= MIN_TICKS_PER_ROW,
+            "refresh rate is too high"
+        );
+        assert!(
+            ticks_per_row * (HW_ROWS as u32 + 1) <= u16::MAX as u32,
+            "refresh rate is too low"
+        );
+        ticks_per_row as u16
+    }
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A timer which can be used to drive the display.
+pub trait DisplayTimer: timer::Instance + sealed::DisplayTimer {}
+
+mod sealed {
+    use embassy::util::Forever;
+    use embassy_hal_common::peripheral::StateStorage;
+    use embassy_nrf::pac;
+
+    pub trait DisplayTimer: Sized {
+        fn regs() -> &'static pac::timer0::RegisterBlock;
+        /// Each timer gets its own storage, since statics can't be generic.
+        fn storage() -> &'static Forever<StateStorage<super::DisplayState<Self>>>
+        where
+            Self: super::DisplayTimer;
+    }
+}
+
+macro_rules! display_timer {
+    ($($timer:ident),*) => {
+        $(
+            impl sealed::DisplayTimer for $timer {
+                fn regs() -> &'static pac::timer0::RegisterBlock {
+                    // TIMER3 and TIMER4 have more CC registers, but are otherwise laid out the same as the rest.
+                    unsafe { &*(pac::$timer::ptr() as *const pac::timer0::RegisterBlock) }
+                }
+
+                fn storage() -> &'static Forever<StateStorage<DisplayState<Self>>> {
+                    static STATE: Forever<StateStorage<DisplayState<$timer>>> = Forever::new();
+                    &STATE
+                }
+            }
+
+            impl DisplayTimer for $timer {}
+        )*
+    };
+}
+
+display_timer!(TIMER0, TIMER1, TIMER2);
+#[cfg(v2)]
+display_timer!(TIMER3, TIMER4);
+
+// This is only public so that `sealed::DisplayTimer` can name it.
+#[doc(hidden)]
+pub struct DisplayState<T: DisplayTimer> {
+    /// The image being displayed, or about to be displayed at the start of the next frame.
+    image: Image,
+    curve: &'static [u8; 256],
+    brightness: u8,
+    /// The on-time for each LED value with the current `curve`, `brightness` and `ticks_per_row`.
+    level_ticks: [u16; 256],
+
+    ticks_per_row: u16,
+    ticks_per_frame: u16,
+
+    next_steps: [[(u16, usize); HW_COLS]; HW_ROWS],
+    steps: [[(u16, usize); HW_COLS]; HW_ROWS],
+
+    row: usize,
+    /// The current 'step' of the current row; the nth step is when the nth dimmest column is turned off.
+    step: usize,
+
+    row_pins: [gpio::Output<'static, AnyPin>; HW_ROWS],
+    col_pins: [gpio::Output<'static, AnyPin>; HW_COLS],
+    /// The `psel_bits` of each column pin, so that they can be briefly turned into inputs to sense light.
+    col_psels: [u32; HW_COLS],
+
+    /// Whether a light reading should be taken at the end of the next frame.
+    light_requested: bool,
+    /// Whether the ADC is currently lent to us by `read_light_level`.
+    light_adc_lent: bool,
+    /// Whether the ADC has been enabled by `prepare_conversion` and not disabled again yet.
+    light_adc_enabled: bool,
+    /// The next step of the current light-sensing row:
+    /// the first step lets the columns float, and then each pair of steps after that prepares and then starts a conversion
+    /// on the next of `LIGHT_INPUTS`.
+    light_step: usize,
+    /// The sum of the ADC readings taken so far.
+    light_total: u32,
+    /// The number of ADC readings in `light_total`.
+    light_readings: usize,
+    /// Where the SAADC writes its results.
+    #[cfg(v2)]
+    light_result: i16,
+    light_level: Option<u8>,
+
+    /// Woken when a light reading is finished.
+    light_waker: WakerRegistration,
+
+    /// The number of frames which have been started, used to tell when a vsync has happened.
+    frame: u32,
+    /// Woken at the start of each frame.
+    vsync_waker: WakerRegistration,
+
+    /// The animation or scrolling text currently playing in the background, if any.
+    background: Option<Background>,
+    /// Woken when a background animation finishes or is cancelled.
+    background_waker: WakerRegistration,
+
+    timer: HwTimer<'static, T, u16>,
+}
+
+impl<T: DisplayTimer> DisplayState<T> {
+    fn set_image(&mut self, image: Image) {
+        self.next_steps = image.steps(&self.level_ticks);
+        self.image = image;
+    }
+
+    /// Changes a single LED of the image, only recomputing the steps for the hardware row it's in.
+    fn set_pixel(&mut self, x: usize, y: usize, value: u8) {
+        self.image[y][x] = value;
+        let row = Image::hw_row_of(x, y);
+        self.next_steps[row] = row_steps(&self.image.hw_rows()[row], &self.level_ticks);
+    }
+
+    /// Starts playing something in the background, replacing whatever was playing before.
+    fn start(&mut self, background: Background, image: Image) {
+        self.stop();
+        self.set_image(image);
+        self.background = Some(background);
+    }
+
+    /// Stops any animation or scrolling text which is playing.
+    fn stop(&mut self) {
+        if self.background.take().is_some() {
+            self.background_waker.wake();
+        }
+    }
+
+    /// Moves anything playing in the background on to its next frame, if it's time to.
+    fn advance_background(&mut self) {
+        if let Some(background) = &mut self.background {
+            let now = Instant::now();
+            let (image, playing) = match background {
+                Background::Animation(player) => player.advance(now),
+                Background::Scroll(player) => player.advance(now),
+            };
+            if let Some(image) = image {
+                self.set_image(image);
+            }
+            if !playing {
+                self.stop();
+            }
+        }
+    }
+
+    /// Changes the brightness settings, along with the `level_ticks` worked out for them, and recomputes `next_steps`.
+    fn set_levels(&mut self, curve: &'static [u8; 256], brightness: u8, level_ticks: [u16; 256]) {
+        self.curve = curve;
+        self.brightness = brightness;
+        self.level_ticks = level_ticks;
+        self.next_steps = self.image.steps(&self.level_ticks);
+    }
+
+    /// Starts the extra row at the end of a frame used to sense light.
+    ///
+    /// The LEDs can act as (very weak) photodiodes: charge them up in reverse, then let the columns float,
+    /// and the brighter it is the faster the light falling on them discharges the columns.
+    fn start_sensing(&mut self) {
+        // At this point the rows are all low and the columns are all high, so the LEDs are already being charged in reverse.
+        self.light_step = 0;
+        self.light_total = 0;
+        self.light_readings = 0;
+    }
+
+    /// Returns the time within the light-sensing row at which the `step`th step should happen.
+    fn light_step_time(&self, step: usize) -> u16 {
+        if step == 0 {
+            LIGHT_CHARGE_TICKS
+        } else {
+            // Leave the columns discharging for as long as possible before sampling them,
+            // while making sure there's time to read the last one before the row ends.
+            let remaining = (LIGHT_INPUTS.len() - (step - 1) / 2) as u16;
+            let start = self
+                .ticks_per_row
+                .saturating_sub(remaining * LIGHT_CONVERSION_TICKS)
+                .max(LIGHT_CHARGE_TICKS + LIGHT_PREPARE_TICKS);
+            if step % 2 == 1 {
+                start - LIGHT_PREPARE_TICKS
+            } else {
+                start
+            }
+        }
+    }
+
+    /// Does any light-sensing steps which are due, and returns the time of the next one.
+    fn sense_light(&mut self) -> u16 {
+        let time = self.time();
+        while self.light_step < LIGHT_STEPS && time >= self.light_step_time(self.light_step) {
+            if self.light_step == 0 {
+                // Charging's done; let the columns float.
+                for &psel in &self.col_psels {
+                    let (port, pin) = gpio_port(psel);
+                    port.pin_cnf[pin].write(|w| {
+                        w.dir().input();
+                        w.input().disconnect();
+                        w.pull().disabled();
+                        w.drive().s0s1();
+                        w.sense().disabled();
+                        w
+                    });
+                }
+            } else if self.light_adc_lent {
+                // The ADC might only have been lent partway through the row, in which case a conversion wasn't prepared.
+                if self.light_step % 2 == 1 {
+                    if self.light_adc_enabled {
+                        self.light_total += self.finish_conversion();
+                        self.light_readings += 1;
+                    }
+                    self.prepare_conversion(LIGHT_INPUTS[(self.light_step - 1) / 2]);
+                } else if self.light_adc_enabled {
+                    self.start_conversion();
+                }
+            }
+
+            self.light_step += 1;
+        }
+
+        if self.light_step < LIGHT_STEPS {
+            self.light_step_time(self.light_step)
+        } else {
+            self.ticks_per_row
+        }
+    }
+
+    /// Puts the columns back to normal after sensing light, and reports the reading.
+    fn finish_sensing(&mut self) {
+        for &psel in &self.col_psels {
+            let (port, pin) = gpio_port(psel);
+            // The output register hasn't been touched, so they'll go straight back to being high.
+            port.pin_cnf[pin].write(|w| {
+                w.dir().output();
+                w.input().disconnect();
+                w.pull().disabled();
+                w.drive().s0s1();
+                w.sense().disabled();
+                w
+            });
+        }
+
+        if self.light_adc_lent && self.light_adc_enabled {
+            self.light_total += self.finish_conversion();
+            self.light_readings += 1;
+        }
+
+        // If `read_light_level` was cancelled partway through, or only started partway through, there's nothing to report.
+        if self.light_adc_lent && self.light_readings == LIGHT_INPUTS.len() {
+            let reading = (self.light_total / LIGHT_INPUTS.len() as u32)
+                .clamp(LIGHT_MIN_READING, LIGHT_MAX_READING);
+            self.light_level = Some(
+                ((LIGHT_MAX_READING - reading) * 255 / (LIGHT_MAX_READING - LIGHT_MIN_READING))
+                    as u8,
+            );
+            self.light_waker.wake();
+        }
+
+        // Go back to normal-length frames.
+        self.timer.cc(1).write(self.ticks_per_frame);
+    }
+
+    /// Sets up the ADC to convert the voltage on analog input `input`, scaled so that 0-1023 covers 0V-VDD.
+    // TODO: Make a proper binding for this.
+    #[cfg(not(v2))]
+    fn prepare_conversion(&mut self, input: u8) {
+        let adc = unsafe { &*pac::ADC::ptr() };
+        adc.enable.write(|w| w.enable().enabled());
+        adc.config.write(|w| {
+            w.res()._10bit();
+            w.inpsel().analog_input_one_third_prescaling();
+            w.refsel().supply_one_third_prescaling();
+            unsafe { w.psel().bits(1 << input) };
+            w.extrefsel().none();
+            w
+        });
+        adc.events_end.reset();
+        self.light_adc_enabled = true;
+    }
+
+    /// Sets up the ADC to convert the voltage on analog input `input`, scaled so that 0-1023 covers 0V-VDD.
+    ///
+    /// The SAADC has to have started before it can take a sample, which is why this is done ahead of `start_conversion`.
+    // TODO: Make a proper binding for this.
+    #[cfg(v2)]
+    fn prepare_conversion(&mut self, input: u8) {
+        let saadc = unsafe { &*pac::SAADC::ptr() };
+        saadc.enable.write(|w| w.enable().enabled());
+        saadc.resolution.write(|w| w.val()._10bit());
+        // `PSELP` counts the analog inputs from 1, since 0 means not connected.
+        saadc.ch[0]
+            .pselp
+            .write(|w| unsafe { w.pselp().bits(input + 1) });
+        saadc.ch[0].pseln.write(|w| w.pseln().nc());
+        saadc.ch[0].config.write(|w| {
+            w.refsel().vdd1_4();
+            w.gain().gain1_4();
+            w.tacq()._10us();
+            w.mode().se();
+            w.resp().bypass();
+            w.resn().bypass();
+            w.burst().disabled();
+            w
+        });
+        saadc
+            .result
+            .ptr
+            .write(|w| unsafe { w.ptr().bits(&mut self.light_result as *mut i16 as u32) });
+        saadc.result.maxcnt.write(|w| unsafe { w.maxcnt().bits(1) });
+
+        saadc.events_started.reset();
+        saadc.events_end.reset();
+        compiler_fence(Ordering::SeqCst);
+        saadc.tasks_start.write(|w| unsafe { w.bits(1) });
+        self.light_adc_enabled = true;
+    }
+
+    /// Starts the conversion set up by `prepare_conversion`.
+    #[cfg(not(v2))]
+    fn start_conversion(&mut self) {
+        let adc = unsafe { &*pac::ADC::ptr() };
+        adc.tasks_start.write(|w| unsafe { w.bits(1) });
+    }
+
+    /// Starts the conversion set up by `prepare_conversion`.
+    #[cfg(v2)]
+    fn start_conversion(&mut self) {
+        let saadc = unsafe { &*pac::SAADC::ptr() };
+        saadc.tasks_sample.write(|w| unsafe { w.bits(1) });
+    }
+
+    /// Waits for the conversion started by `start_conversion` to finish, and returns its result.
+    ///
+    /// There's normally enough time left for it that this doesn't have to wait, unless the rows are very short.
+    // TODO: Make a proper binding for this.
+    #[cfg(not(v2))]
+    fn finish_conversion(&mut self) -> u32 {
+        let adc = unsafe { &*pac::ADC::ptr() };
+        while adc.events_end.read().bits() == 0 {}
+        adc.events_end.reset();
+        let result = adc.result.read().result().bits() as u32;
+        adc.enable.write(|w| w.enable().disabled());
+        self.light_adc_enabled = false;
+        result
+    }
+
+    /// Waits for the conversion started by `start_conversion` to finish, and returns its result.
+    ///
+    /// There's normally enough time left for it that this doesn't have to wait, unless the rows are very short.
+    // TODO: Make a proper binding for this.
+    #[cfg(v2)]
+    fn finish_conversion(&mut self) -> u32 {
+        let saadc = unsafe { &*pac::SAADC::ptr() };
+        while saadc.events_end.read().bits() == 0 {}
+        saadc.events_end.reset();
+        saadc.events_started.reset();
+        saadc.enable.write(|w| w.enable().disabled());
+        self.light_adc_enabled = false;
+        compiler_fence(Ordering::SeqCst);
+        // Noise can make it come out slightly negative.
+        self.light_result.max(0) as u32
+    }
+
+    /// Abandons any conversion in progress and turns the ADC off, for when `read_light_level` is cancelled.
+    // TODO: Make a proper binding for this.
+    #[cfg(not(v2))]
+    fn stop_conversion(&mut self) {
+        let adc = unsafe { &*pac::ADC::ptr() };
+        adc.tasks_stop.write(|w| unsafe { w.bits(1) });
+        adc.events_end.reset();
+        adc.enable.write(|w| w.enable().disabled());
+        self.light_adc_enabled = false;
+    }
+
+    /// Abandons any conversion in progress and turns the ADC off, for when `read_light_level` is cancelled.
+    // TODO: Make a proper binding for this.
+    #[cfg(v2)]
+    fn stop_conversion(&mut self) {
+        let saadc = unsafe { &*pac::SAADC::ptr() };
+        saadc.tasks_stop.write(|w| unsafe { w.bits(1) });
+        saadc.events_started.reset();
+        saadc.events_end.reset();
+        saadc.events_done.reset();
+        saadc.events_resultdone.reset();
+        saadc.events_stopped.reset();
+        saadc.enable.write(|w| w.enable().disabled());
+        self.light_adc_enabled = false;
+        compiler_fence(Ordering::SeqCst);
+    }
+
+    fn time(&mut self) -> u16 {
+        // Don't use a modulus for this so that things don't get messed up
+        // if the timer hits the next row midway through the interrupt.
+        self.timer.cc(2).capture() - self.row as u16 * self.ticks_per_row
+    }
+}
+
+impl<T: DisplayTimer> PeripheralState for DisplayState<T> {
+    type Interrupt = T::Interrupt;
+
+    // This is written to do everything based on the timer's current value, rather then the numbre of times it's triggered.
+    fn on_interrupt(&mut self) {
+        // Clear the events so this interrupt doesn't get repeatedly fired.
+        // TODO: Make a proper binding for this.
+        let reg = T::regs();
+        reg.events_compare[0].reset();
+        reg.events_compare[1].reset();
+
+        let row = self.timer.cc(2).capture() / self.ticks_per_row;
+        // Row `HW_ROWS` is the extra row used for sensing light.
+        let row = (row as usize).min(HW_ROWS);
+
+        if row != self.row {
+            // The row has changed; start rendering a new one.
+
+            if self.row == HW_ROWS {
+                self.finish_sensing();
+            } else {
+                // Turn off any remaining columns.
+                for pin in &mut self.col_pins {
+                    pin.set_high().unwrap();
+                }
+
+                // Disable the previous row's pin.
+                self.row_pins[self.row].set_low().unwrap();
+            }
+
+            self.row = row;
+            self.step = 0;
+
+            if self.row == HW_ROWS {
+                self.start_sensing();
+            } else {
+                if self.row == 0 {
+                    self.advance_background();
+
+                    // Update the image we're displaying at the start of each frame.
+                    self.steps = self.next_steps;
+
+                    self.frame = self.frame.wrapping_add(1);
+                    self.vsync_waker.wake();
+
+                    if self.light_requested {
+                        // Add the extra row onto the end of this frame.
+                        self.light_requested = false;
+                        self.timer
+                            .cc(1)
+                            .write(self.ticks_per_frame + self.ticks_per_row);
+                    }
+                }
+
+                self.row_pins[self.row].set_high().unwrap();
+
+                // Turn on all the pins which aren't supposed to be completely off.
+                for (time, col) in self.steps[self.row] {
+                    if time > 0 {
+                        // The column pins are active low.
+                        self.col_pins[col].set_low().unwrap();
+                    } else {
+                        // We don't need to step through any columns which weren't on to begin with.
+                        self.step += 1;
+                    }
+                }
+            }
+        }
+
+        let time = if self.row == HW_ROWS {
+            self.sense_light()
+        } else {
+            let steps = self.steps[self.row];
+
+            // Turn off all of the columns whose times have passed.
+            while self.step < HW_COLS && steps[self.step].0 <= self.time() {
+                let (_, col) = steps[self.step];
+
+                self.col_pins[col].set_high().unwrap();
+
+                self.step += 1;
+            }
+
+            self.steps[self.row]
+                .get(self.step)
+                // Default to `ticks_per_row` if there are none left, since we then just want to wait until we reach the next row.
+                .map_or(self.ticks_per_row, |&(time, _)| time)
+        };
+
+        self.timer
+            .cc(0)
+            .write(self.row as u16 * self.ticks_per_row + time);
+
+        // Start the timer if it isn't already running.
+        self.timer.start();
+
+        if self.timer.cc(2).capture() >= self.timer.cc(0).read()
+            && !unsafe { T::Interrupt::steal() }.is_pending()
+        {
+            // It ticked past between the loop and here, so just trigger this handler again.
+            self.on_interrupt();
+        }
+    }
+}
+
+/// Stops the display from using the ADC once `read_light_level` has finished or been cancelled.
+struct LightReading<'a, T: DisplayTimer>(&'a mut Display<T>);
+
+impl<T: DisplayTimer> Drop for LightReading<'_, T> {
+    fn drop(&mut self) {
+        self.0.mutex.with(|state| {
+            state.light_requested = false;
+            state.light_adc_lent = false;
+            // If this was cancelled partway through a reading, the interrupt won't finish the conversion it started.
+            if state.light_adc_enabled {
+                state.stop_conversion();
+            }
+        });
+    }
+}
+
+/// Something which the display's interrupt keeps updating by itself.
+enum Background {
+    Animation(AnimationPlayer),
+    Scroll(ScrollPlayer),
+}
+
+pub struct Display<T: DisplayTimer = TIMER1> {
+    mutex: PeripheralMutex<'static, DisplayState<T>>,
+}
+
+impl<T: DisplayTimer> Display<T> {
+    /// Spawns a task to drive the display and returns a handle to set the display's image.
+    pub fn new(pins: Pins, timer: T, irq: T::Interrupt) -> Self {
+        Self::with_config(pins, timer, irq, DisplayConfig::DEFAULT)
+    }
+
+    /// Like `new`, but with custom settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.refresh_rate` is out of range: it has to be at least about 20Hz,
+    /// and at most about 780Hz on a micro:bit v2 or 1300Hz on a v1.
+    pub fn with_config(pins: Pins, timer: T, irq: T::Interrupt, config: DisplayConfig) -> Self {
+        let ticks_per_row = config.ticks_per_row();
+        // Base this on the smaller value to make sure it's a clean multiple.
+        let ticks_per_frame = ticks_per_row * HW_ROWS as u16;
+
+        #[cfg(v2)]
+        let col_psels = [
+            pins.col1.psel_bits(),
+            pins.col2.psel_bits(),
+            pins.col3.psel_bits(),
+            pins.col4.psel_bits(),
+            pins.col5.psel_bits(),
+        ];
+        #[cfg(not(v2))]
+        let col_psels = [
+            pins.col1.psel_bits(),
+            pins.col2.psel_bits(),
+            pins.col3.psel_bits(),
+            pins.col4.psel_bits(),
+            pins.col5.psel_bits(),
+            pins.col6.psel_bits(),
+            pins.col7.psel_bits(),
+            pins.col8.psel_bits(),
+            pins.col9.psel_bits(),
+        ];
+
+        let level_ticks = level_ticks(&GAMMA, 255, ticks_per_row);
+
+        let mut state = DisplayState {
+            timer: HwTimer::new(timer),
+
+            image: Image::BLANK,
+            curve: &GAMMA,
+            brightness: 255,
+            level_ticks,
+
+            ticks_per_row,
+            ticks_per_frame,
+
+            next_steps: Image::BLANK.steps(&level_ticks),
+            steps: Image::BLANK.steps(&level_ticks),
+            // Initialize the state such that it'll immediately reset itself.
+            row: HW_ROWS - 1,
+            step: HW_COLS,
+
+            #[cfg(v2)]
+            row_pins: [
+                gpio::Output::new(pins.row1.degrade(), Level::Low, OutputDrive::Standard),
+                gpio::Output::new(pins.row2.degrade(), Level::Low, OutputDrive::Standard),
+                gpio::Output::new(pins.row3.degrade(), Level::Low, OutputDrive::Standard),
+                gpio::Output::new(pins.row4.degrade(), Level::Low, OutputDrive::Standard),
+                gpio::Output::new(pins.row5.degrade(), Level::Low, OutputDrive::Standard),
+            ],
+            #[cfg(not(v2))]
+            row_pins: [
+                gpio::Output::new(pins.row1.degrade(), Level::Low, OutputDrive::Standard),
+                gpio::Output::new(pins.row2.degrade(), Level::Low, OutputDrive::Standard),
+                gpio::Output::new(pins.row3.degrade(), Level::Low, OutputDrive::Standard),
+            ],
+
+            #[cfg(v2)]
+            col_pins: [
+                gpio::Output::new(pins.col1.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col2.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col3.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col4.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col5.degrade(), Level::High, OutputDrive::Standard),
+            ],
+            #[cfg(not(v2))]
+            col_pins: [
+                gpio::Output::new(pins.col1.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col2.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col3.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col4.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col5.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col6.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col7.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col8.degrade(), Level::High, OutputDrive::Standard),
+                gpio::Output::new(pins.col9.degrade(), Level::High, OutputDrive::Standard),
+            ],
+            col_psels,
+
+            light_requested: false,
+            light_adc_lent: false,
+            light_adc_enabled: false,
+            light_step: 0,
+            light_total: 0,
+            light_readings: 0,
+            #[cfg(v2)]
+            light_result: 0,
+            light_level: None,
+
+            light_waker: WakerRegistration::new(),
+
+            frame: 0,
+            vsync_waker: WakerRegistration::new(),
+
+            background: None,
+            background_waker: WakerRegistration::new(),
+        };
+
+        // Make the timer reset itself at the end of each frame.
+        state.timer.cc(1).write(ticks_per_frame);
+        state.timer.cc(1).short_compare_clear();
+        // Enable an interrupt when CC 0 or 1's value is reached.
+        // TODO: Make a proper binding for this.
+        T::regs()
+            .intenset
+            .write(|w| w.compare0().set_bit().compare1().set_bit());
+
+        irq.pend();
+
+        let mutex = PeripheralMutex::new(irq, T::storage().put(StateStorage::new()), || state);
+
+        Self { mutex }
+    }
+
+    /// Shows `image`, stopping any animation which is playing.
+    pub fn show(&mut self, image: Image) {
+        self.mutex.with(|state| {
+            state.stop();
+            state.set_image(image);
+        });
+    }
+
+    /// Sets the brightness of the LED at `(x, y)`, stopping any animation which is playing.
+    ///
+    /// This is cheaper than `show`ing a whole new image, since only the part of the display the LED is in needs to be worked out again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is 5 or more.
+    pub fn set_pixel(&mut self, x: usize, y: usize, value: u8) {
+        assert!(x < 5 && y < 5, "pixel ({}, {}) is off the display", x, y);
+        self.mutex.with(|state| {
+            state.stop();
+            state.set_pixel(x, y, value);
+        });
+    }
+
+    /// Returns the brightness of the LED at `(x, y)` in the image being shown.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is 5 or more.
+    pub fn get_pixel(&mut self, x: usize, y: usize) -> u8 {
+        assert!(x < 5 && y < 5, "pixel ({}, {}) is off the display", x, y);
+        self.mutex.with(|state| state.image[y][x])
+    }
+
+    /// Returns the image being shown, including any changes made with `set_pixel`.
+    pub fn image(&mut self) -> Image {
+        self.mutex.with(|state| state.image.clone())
+    }
+
+    /// Shows a single character from the font.
+    pub fn show_char(&mut self, char: char) {
+        self.show(Image::from(char));
+    }
+
+    /// Shows each character of `text` in turn for `delay`, without scrolling, like MicroPython's `display.show`.
+    ///
+    /// If `clear` is set the display is cleared once the last character's been shown for `delay`, otherwise it's left showing.
+    pub async fn show_str(&mut self, text: &str, delay: Duration, clear: bool) {
+        for char in text.chars() {
+            self.show_char(char);
+            Timer::after(delay).await;
+        }
+
+        if clear {
+            self.show(Image::BLANK);
+        }
+    }
+
+    /// Shows `image`, and then waits until it's actually started being displayed.
+    ///
+    /// This makes for smoother animations than a separate timer, since every image gets shown for exactly one frame.
+    pub async fn show_and_wait_vsync(&mut self, image: Image) {
+        let frame = self.mutex.with(|state| {
+            state.stop();
+            state.set_image(image);
+            state.frame
+        });
+        self.wait_for_frame_after(frame).await;
+    }
+
+    /// Waits for the start of the next frame, which is when the last image passed to `show` starts being displayed.
+    pub async fn vsync(&mut self) {
+        let frame = self.mutex.with(|state| state.frame);
+        self.wait_for_frame_after(frame).await;
+    }
+
+    async fn wait_for_frame_after(&mut self, frame: u32) {
+        poll_fn(|cx| {
+            self.mutex.with(|state| {
+                if state.frame != frame {
+                    Poll::Ready(())
+                } else {
+                    state.vsync_waker.register(cx.waker());
+                    Poll::Pending
+                }
+            })
+        })
+        .await
+    }
+
+    /// Plays `animation`, waiting until it finishes.
+    ///
+    /// The animation is driven by the display's interrupt, so it keeps playing even if this future is dropped,
+    /// until it's replaced by a call to `show` or another animation, or `stop` is called.
+    /// That means a looping animation can be left running in the background while doing other things.
+    pub async fn play(&mut self, animation: &Animation<'static>) {
+        let started =
+            self.mutex.with(
+                |state| match AnimationPlayer::start(*animation, Instant::now()) {
+                    Some((player, image)) => {
+                        state.start(Background::Animation(player), image);
+                        true
+                    }
+                    None => {
+                        state.stop();
+                        false
+                    }
+                },
+            );
+
+        if !started {
+            return;
+        }
+
+        poll_fn(|cx| {
+            self.mutex.with(|state| {
+                if state.background.is_none() {
+                    Poll::Ready(())
+                } else {
+                    state.background_waker.register(cx.waker());
+                    Poll::Pending
+                }
+            })
+        })
+        .await
+    }
+
+    /// Starts scrolling `text` across the display in the background, without waiting for it to finish.
+    ///
+    /// If `looping` is set, the text keeps scrolling until `stop` or `show` is called.
+    pub fn start_scroll(&mut self, text: &'static str, looping: bool) {
+        self.start_scroll_with(text, looping, ScrollOptions::DEFAULT)
+    }
+
+    /// Like `start_scroll`, but with custom speed, direction and spacing.
+    pub fn start_scroll_with(&mut self, text: &'static str, looping: bool, options: ScrollOptions) {
+        self.mutex.with(|state| {
+            let (player, image) = ScrollPlayer::start(text, looping, options, Instant::now());
+            state.start(Background::Scroll(player), image);
+        });
+    }
+
+    /// Returns whether text started with `start_scroll` is still scrolling.
+    pub fn is_scrolling(&mut self) -> bool {
+        self.mutex
+            .with(|state| matches!(state.background, Some(Background::Scroll(_))))
+    }
+
+    /// Stops any text scrolling or animation playing in the background, leaving whatever was last shown on the display.
+    pub fn stop(&mut self) {
+        self.mutex.with(|state| state.stop());
+    }
+
+    pub fn brightness(&mut self) -> u8 {
+        self.mutex.with(|state| state.brightness)
+    }
+
+    /// Scales the brightness of every LED, without needing to `show` the image again.
+    pub fn set_brightness(&mut self, brightness: u8) {
+        let curve = self.mutex.with(|state| state.curve);
+        self.set_levels(curve, brightness);
+    }
+
+    /// Measures the ambient light level using the LEDs, returning a value from 0 (dark) to 255 (bright).
+    ///
+    /// This adds an extra row's worth of time onto the end of one frame, so the display will flicker very slightly.
+    /// The ADC is only used during that row, but it has to be lent to the display until the reading's done.
+    pub async fn read_light_level(&mut self, _adc: &mut LightSensorAdc) -> u8 {
+        self.mutex.with(|state| {
+            state.light_requested = true;
+            state.light_adc_lent = true;
+            state.light_level = None;
+        });
+
+        let mut reading = LightReading(self);
+        poll_fn(|cx| {
+            reading
+                .0
+                .mutex
+                .with(|state| match state.light_level.take() {
+                    Some(level) => Poll::Ready(level),
+                    None => {
+                        state.light_waker.register(cx.waker());
+                        Poll::Pending
+                    }
+                })
+        })
+        .await
+    }
+
+    /// Sets the curve used to map LED values to on-time.
+    ///
+    /// This defaults to `GAMMA`; `LINEAR` can be used to get back the old behaviour, or you can provide your own lookup table.
+    pub fn set_brightness_curve(&mut self, curve: &'static [u8; 256]) {
+        let brightness = self.mutex.with(|state| state.brightness);
+        self.set_levels(curve, brightness);
+    }
+
+    fn set_levels(&mut self, curve: &'static [u8; 256], brightness: u8) {
+        let ticks_per_row = self.mutex.with(|state| state.ticks_per_row);
+        // This takes a division per LED value, so it's done outside of `mutex.with` to avoid holding up the display's interrupt.
+        // Nothing else changes the brightness settings in the meantime, since that needs `&mut self`.
+        let level_ticks = level_ticks(curve, brightness, ticks_per_row);
+        self.mutex
+            .with(|state| state.set_levels(curve, brightness, level_ticks));
+    }
+
+    pub async fn scroll(&mut self, text: &str) {
+        self.scroll_with(text, ScrollOptions::DEFAULT).await
+    }
+
+    /// Scrolls `text` across the display, with custom speed, direction and spacing.
+    pub async fn scroll_with(&mut self, text: &str, options: ScrollOptions) {
+        if options.direction.reversed() {
+            self.run_scroller(Scroller::new(text.chars().rev(), options))
+                .await
+        } else {
+            self.run_scroller(Scroller::new(text.chars(), options))
+                .await
+        }
+    }
+
+    /// Scrolls some formatted text across the display, such as `format_args!("T={}C", temperature)`.
+    ///
+    /// This doesn't allocate: instead, the text gets formatted again for every character,
+    /// so it has to come out the same every time it's formatted.
+    pub async fn scroll_fmt(&mut self, args: fmt::Arguments<'_>) {
+        self.scroll_fmt_with(args, ScrollOptions::DEFAULT).await
+    }
+
+    /// Like `scroll_fmt`, but with custom speed, direction and spacing.
+    pub async fn scroll_fmt_with(&mut self, args: fmt::Arguments<'_>, options: ScrollOptions) {
+        if options.direction.reversed() {
+            self.run_scroller(Scroller::new(FmtChars::new(args).rev(), options))
+                .await
+        } else {
+            self.run_scroller(Scroller::new(FmtChars::new(args), options))
+                .await
+        }
+    }
+
+    async fn run_scroller<I: Iterator<Item = char>>(&mut self, mut scroller: Scroller<I>) {
+        let delay = scroller.options().delay;
+        while let Some(image) = scroller.step() {
+            self.show(image);
+            Timer::after(delay).await;
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! The font used to turn characters into images, which can be extended with custom glyphs.
+//!
+//! The built-in font covers printable ASCII, Latin-1 apart from the fractions ¼, ½ and ¾, and a few other symbols like arrows.
+
+use core::cell::Cell;
+use core::cell::RefCell;
+
+use bare_metal::Mutex;
+
+use super::Image;
+
+/// A 'compressed' 5x5 image, where each row is a u8 with each bit representing an LED being on or off.
+///
+/// The most significant of the bottom 5 bits is the leftmost LED; `Image::from` turns these into full images.
+pub type Glyph = [u8; 5];
+
+/// The maximum number of glyphs which can be registered with `register_glyph`.
+pub const MAX_CUSTOM_GLYPHS: usize = 16;
+
+/// The glyph shown for characters which aren't in the font: a question mark.
+pub const UNKNOWN: Glyph = [0b01110, 0b10001, 0b00110, 0b00000, 0b00100];
+
+/// The slots for glyphs registered with `register_glyph`, which are `None` when unused.
+type CustomGlyphs = [Option<(char, Glyph)>; MAX_CUSTOM_GLYPHS];
+
+static CUSTOM_GLYPHS: Mutex<RefCell<CustomGlyphs>> =
+    Mutex::new(RefCell::new([None; MAX_CUSTOM_GLYPHS]));
+static GLYPH_TABLE: Mutex<Cell<&'static [(char, Glyph)]>> = Mutex::new(Cell::new(&[]));
+
+/// Adds a glyph to the font at runtime, replacing any existing glyph for `char`.
+///
+/// # Panics
+///
+/// Panics if there are already `MAX_CUSTOM_GLYPHS` glyphs registered, not counting one for `char`.
+pub fn register_glyph(char: char, glyph: Glyph) {
+    critical_section::with(|cs| {
+        let mut glyphs = CUSTOM_GLYPHS.borrow(cs).borrow_mut();
+        let slot = match glyphs
+            .iter()
+            .position(|entry| matches!(entry, Some((c, _)) if *c == char))
+        {
+            Some(i) => &mut glyphs[i],
+            None => glyphs
+                .iter_mut()
+                .find(|entry| entry.is_none())
+                .expect("too many custom glyphs"),
+        };
+        *slot = Some((char, glyph));
+    })
+}
+
+/// Removes a glyph added with `register_glyph`, so that `char` goes back to how it was before.
+pub fn unregister_glyph(char: char) {
+    critical_section::with(|cs| {
+        for entry in CUSTOM_GLYPHS.borrow(cs).borrow_mut().iter_mut() {
+            if matches!(entry, Some((c, _)) if *c == char) {
+                *entry = None;
+            }
+        }
+    })
+}
+
+/// Sets a table of extra glyphs to add to the font, replacing any previous table.
+///
+/// Unlike `register_glyph` there's no limit on its size, since it can be a `static` or `const` and live in flash.
+pub fn set_glyph_table(table: &'static [(char, Glyph)]) {
+    critical_section::with(|cs| GLYPH_TABLE.borrow(cs).set(table))
+}
+
+/// Looks up the glyph for `char`, falling back to `UNKNOWN` if there isn't one.
+///
+/// Glyphs added with `register_glyph` take priority, followed by the table passed to `set_glyph_table`, and then the built-in font.
+pub fn glyph(char: char) -> Glyph {
+    let custom = critical_section::with(|cs| {
+        CUSTOM_GLYPHS
+            .borrow(cs)
+            .borrow()
+            .iter()
+            .flatten()
+            .chain(GLYPH_TABLE.borrow(cs).get())
+            .find(|&&(c, _)| c == char)
+            .map(|&(_, glyph)| glyph)
+    });
+
+    custom.or_else(|| builtin(char)).unwrap_or(UNKNOWN)
+}
+
+impl From<char> for Image {
+    fn from(char: char) -> Self {
+        Image::unpack(glyph(char))
+    }
+}
+
+// Accents, positioned over a letter 4 columns wide.
+const GRAVE: u8 = 0b01000;
+const ACUTE: u8 = 0b00100;
+const CIRCUMFLEX: u8 = 0b01100;
+const TILDE: u8 = 0b11110;
+const DIAERESIS: u8 = 0b10010;
+
+// `I` is narrower than the other letters, so it needs its own accents to keep them centred.
+const I_GRAVE: u8 = 0b10000;
+const I_ACUTE: u8 = 0b00100;
+const I_CIRCUMFLEX: u8 = 0b01000;
+const I_DIAERESIS: u8 = 0b10100;
+
+// Letters squashed into 4 rows, to leave room for an accent on top.
+const A: [u8; 4] = [0b01100, 0b10010, 0b11110, 0b10010];
+const E: [u8; 4] = [0b11110, 0b11100, 0b10000, 0b11110];
+const I: [u8; 4] = [0b11100, 0b01000, 0b01000, 0b11100];
+const N: [u8; 4] = [0b10010, 0b11010, 0b10110, 0b10010];
+const O: [u8; 4] = [0b01100, 0b10010, 0b10010, 0b01100];
+const U: [u8; 4] = [0b10010, 0b10010, 0b10010, 0b01100];
+const Y: [u8; 4] = [0b10001, 0b01010, 0b00100, 0b00100];
+const LOWER_A: [u8; 4] = [0b01110, 0b10010, 0b10010, 0b01111];
+const LOWER_E: [u8; 4] = [0b01100, 0b11110, 0b10000, 0b01110];
+const LOWER_I: [u8; 4] = [0b00000, 0b01000, 0b01000, 0b01000];
+const LOWER_N: [u8; 4] = [0b11100, 0b10010, 0b10010, 0b10010];
+const LOWER_O: [u8; 4] = [0b01100, 0b10010, 0b10010, 0b01100];
+const LOWER_U: [u8; 4] = [0b10010, 0b10010, 0b10010, 0b01111];
+const LOWER_Y: [u8; 4] = [0b10001, 0b01010, 0b00100, 0b11000];
+
+const fn accented(accent: u8, letter: [u8; 4]) -> Glyph {
+    [accent, letter[0], letter[1], letter[2], letter[3]]
+}
+
+fn builtin(char: char) -> Option<Glyph> {
+    Some(match char {
+        ' ' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000],
+        '!' => [0b01000, 0b01000, 0b01000, 0b00000, 0b01000],
+        '"' => [0b01010, 0b01010, 0b00000, 0b00000, 0b00000],
+        '#' => [0b01010, 0b11111, 0b01010, 0b11111, 0b01010],
+        '$' => [0b01110, 0b11001, 0b01110, 0b10011, 0b01110],
+        '%' => [0b11001, 0b10010, 0b00100, 0b01001, 0b10011],
+        '&' => [0b01100, 0b10010, 0b01100, 0b10010, 0b01101],
+        '\'' => [0b01000, 0b01000, 0b00000, 0b00000, 0b00000],
+        '(' => [0b00100, 0b01000, 0b01000, 0b01000, 0b00100],
+        ')' => [0b01000, 0b00100, 0b00100, 0b00100, 0b01000],
+        '*' => [0b00000, 0b01010, 0b00100, 0b01010, 0b00000],
+        '+' => [0b00000, 0b00100, 0b01110, 0b00100, 0b00000],
+        ',' => [0b00000, 0b00000, 0b00000, 0b00100, 0b01000],
+        '-' => [0b00000, 0b00000, 0b01110, 0b00000, 0b00000],
+        '.' => [0b00000, 0b00000, 0b00000, 0b01000, 0b00000],
+        '/' => [0b00001, 0b00010, 0b00100, 0b01000, 0b10000],
+        '0' => [0b01100, 0b10010, 0b10010, 0b10010, 0b01100],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b11100, 0b00010, 0b01100, 0b10000, 0b11110],
+        '3' => [0b11110, 0b00010, 0b00100, 0b10010, 0b01100],
+        '4' => [0b00110, 0b01010, 0b10010, 0b11111, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b11110],
+        '6' => [0b00010, 0b00100, 0b01110, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00010, 0b00100, 0b01000, 0b10000],
+        '8' => [0b01110, 0b10001, 0b01110, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b01110, 0b00100, 0b01000],
+        ':' => [0b00000, 0b01000, 0b00000, 0b01000, 0b00000],
+        ';' => [0b00000, 0b00100, 0b00000, 0b00100, 0b01000],
+        '<' => [0b00010, 0b00100, 0b01000, 0b00100, 0b00010],
+        '=' => [0b00000, 0b01110, 0b00000, 0b01110, 0b00000],
+        '>' => [0b01000, 0b00100, 0b00010, 0b00100, 0b01000],
+        '@' => [0b01110, 0b10001, 0b10101, 0b10011, 0b01100],
+        'A' => [0b01100, 0b10010, 0b11110, 0b10010, 0b10010],
+        'B' => [0b11100, 0b10010, 0b11100, 0b10010, 0b11100],
+        'C' => [0b01110, 0b10000, 0b10000, 0b10000, 0b01110],
+        'D' => [0b11100, 0b10010, 0b10010, 0b10010, 0b11100],
+        'E' => [0b11110, 0b10000, 0b11100, 0b10000, 0b11110],
+        'F' => [0b11110, 0b10000, 0b11100, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10000, 0b10011, 0b10001, 0b01110],
+        'H' => [0b10010, 0b10010, 0b11110, 0b10010, 0b10010],
+        'I' => [0b11100, 0b01000, 0b01000, 0b01000, 0b11100],
+        'J' => [0b11111, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10010, 0b10100, 0b11000, 0b10100, 0b10010],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b11110],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10001, 0b10001],
+        'N' => [0b10001, 0b11001, 0b10101, 0b10011, 0b10001],
+        'O' => [0b01100, 0b10010, 0b10010, 0b10010, 0b01100],
+        'P' => [0b11100, 0b10010, 0b11100, 0b10000, 0b10000],
+        'Q' => [0b01100, 0b10010, 0b10010, 0b01100, 0b00110],
+        'R' => [0b11100, 0b10010, 0b11100, 0b10010, 0b10001],
+        'S' => [0b01110, 0b10000, 0b01100, 0b00010, 0b11100],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10010, 0b10010, 0b10010, 0b10010, 0b01100],
+        'V' => [0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10101, 0b11011, 0b10001],
+        'X' => [0b10010, 0b10010, 0b01100, 0b10010, 0b10010],
+        'Y' => [0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11110, 0b00100, 0b01000, 0b10000, 0b11110],
+        '[' => [0b01110, 0b01000, 0b01000, 0b01000, 0b01110],
+        '\\' => [0b10000, 0b01000, 0b00100, 0b00010, 0b00001],
+        ']' => [0b01110, 0b00010, 0b00010, 0b00010, 0b01110],
+        '^' => [0b00100, 0b01010, 0b00000, 0b00000, 0b00000],
+        '_' => [0b00000, 0b00000, 0b00000, 0b00000, 0b11111],
+        '`' => [0b01000, 0b00100, 0b00000, 0b00000, 0b00000],
+        'a' => [0b00000, 0b01110, 0b10010, 0b10010, 0b01111],
+        'b' => [0b10000, 0b10000, 0b11100, 0b10010, 0b11100],
+        'c' => [0b00000, 0b01110, 0b10000, 0b10000, 0b01110],
+        'd' => [0b00010, 0b00010, 0b01110, 0b10010, 0b01110],
+        'e' => [0b01100, 0b10010, 0b11100, 0b10000, 0b01110],
+        'f' => [0b00110, 0b01000, 0b11100, 0b01000, 0b01000],
+        'g' => [0b01110, 0b10010, 0b01110, 0b00010, 0b01100],
+        'h' => [0b10000, 0b10000, 0b11100, 0b10010, 0b10010],
+        'i' => [0b01000, 0b00000, 0b01000, 0b01000, 0b01000],
+        'j' => [0b00010, 0b00000, 0b00010, 0b00010, 0b01100],
+        'k' => [0b10000, 0b10100, 0b11000, 0b10100, 0b10010],
+        'l' => [0b01000, 0b01000, 0b01000, 0b01000, 0b00110],
+        'm' => [0b00000, 0b11011, 0b10101, 0b10001, 0b10001],
+        'n' => [0b00000, 0b11100, 0b10010, 0b10010, 0b10010],
+        'o' => [0b00000, 0b01100, 0b10010, 0b10010, 0b01100],
+        'p' => [0b00000, 0b11100, 0b10010, 0b11100, 0b10000],
+        'q' => [0b00000, 0b01110, 0b10010, 0b01110, 0b00010],
+        'r' => [0b00000, 0b01110, 0b10000, 0b10000, 0b10000],
+        's' => [0b00000, 0b00110, 0b01000, 0b00100, 0b11000],
+        't' => [0b01000, 0b01000, 0b01110, 0b01000, 0b00111],
+        'u' => [0b00000, 0b10010, 0b10010, 0b10010, 0b01111],
+        'v' => [0b00000, 0b10001, 0b10001, 0b01010, 0b00100],
+        'w' => [0b00000, 0b10001, 0b10001, 0b10101, 0b11011],
+        'x' => [0b00000, 0b10010, 0b01100, 0b01100, 0b10010],
+        'y' => [0b00000, 0b10001, 0b01010, 0b00100, 0b11000],
+        'z' => [0b00000, 0b11110, 0b00100, 0b01000, 0b11110],
+        '{' => [0b00110, 0b00100, 0b01100, 0b00100, 0b00110],
+        '|' => [0b01000, 0b01000, 0b01000, 0b01000, 0b01000],
+        '}' => [0b11000, 0b01000, 0b01100, 0b01000, 0b11000],
+        '~' => [0b00000, 0b00000, 0b01100, 0b00011, 0b00000],
+
+        // Latin-1 Supplement
+        // The fractions ¼, ½ and ¾ are deliberately left out, since there's no way to make them legible in 5x5.
+        '\u{a0}' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000],
+        '¡' => [0b01000, 0b00000, 0b01000, 0b01000, 0b01000],
+        '¢' => [0b00100, 0b01110, 0b10000, 0b01110, 0b00100],
+        '£' => [0b00110, 0b01001, 0b11100, 0b01000, 0b11111],
+        '¤' => [0b10001, 0b01110, 0b01010, 0b01110, 0b10001],
+        '¥' => [0b10001, 0b01010, 0b11111, 0b00100, 0b11111],
+        '¦' => [0b01000, 0b01000, 0b00000, 0b01000, 0b01000],
+        '§' => [0b01100, 0b10000, 0b10010, 0b00010, 0b01100],
+        '¨' => [0b01010, 0b00000, 0b00000, 0b00000, 0b00000],
+        '©' => [0b01110, 0b10111, 0b11001, 0b10111, 0b01110],
+        'ª' => [0b01110, 0b10010, 0b01110, 0b00000, 0b11110],
+        '«' => [0b00000, 0b01001, 0b10010, 0b01001, 0b00000],
+        '¬' => [0b00000, 0b00000, 0b11110, 0b00010, 0b00000],
+        '\u{ad}' => [0b00000, 0b00000, 0b01110, 0b00000, 0b00000],
+        '®' => [0b01110, 0b11101, 0b11101, 0b11011, 0b01110],
+        '¯' => [0b11110, 0b00000, 0b00000, 0b00000, 0b00000],
+        '°' => [0b01100, 0b10010, 0b01100, 0b00000, 0b00000],
+        '±' => [0b00100, 0b01110, 0b00100, 0b00000, 0b01110],
+        '²' => [0b11000, 0b00100, 0b01000, 0b11100, 0b00000],
+        '³' => [0b11000, 0b01100, 0b00100, 0b11000, 0b00000],
+        '´' => [0b00100, 0b01000, 0b00000, 0b00000, 0b00000],
+        'µ' => [0b00000, 0b10010, 0b10010, 0b11100, 0b10000],
+        '¶' => [0b01111, 0b11101, 0b01101, 0b00101, 0b00101],
+        '·' => [0b00000, 0b00000, 0b01000, 0b00000, 0b00000],
+        '¸' => [0b00000, 0b00000, 0b00000, 0b01000, 0b11000],
+        '¹' => [0b01000, 0b11000, 0b01000, 0b11100, 0b00000],
+        'º' => [0b01100, 0b10010, 0b01100, 0b00000, 0b11110],
+        '»' => [0b00000, 0b10010, 0b01001, 0b10010, 0b00000],
+        '¿' => [0b00100, 0b00000, 0b01100, 0b10001, 0b01110],
+        'À' => accented(GRAVE, A),
+        'Á' => accented(ACUTE, A),
+        'Â' => accented(CIRCUMFLEX, A),
+        'Ã' => accented(TILDE, A),
+        'Ä' => accented(DIAERESIS, A),
+        'Å' => [0b01100, 0b01100, 0b10010, 0b11110, 0b10010],
+        'Æ' => [0b01111, 0b10100, 0b11110, 0b10100, 0b10111],
+        'Ç' => [0b01110, 0b10000, 0b10000, 0b01110, 0b00100],
+        'È' => accented(GRAVE, E),
+        'É' => accented(ACUTE, E),
+        'Ê' => accented(CIRCUMFLEX, E),
+        'Ë' => accented(DIAERESIS, E),
+        'Ì' => accented(I_GRAVE, I),
+        'Í' => accented(I_ACUTE, I),
+        'Î' => accented(I_CIRCUMFLEX, I),
+        'Ï' => accented(I_DIAERESIS, I),
+        'Ð' => [0b11100, 0b10010, 0b11010, 0b10010, 0b11100],
+        'Ñ' => accented(TILDE, N),
+        'Ò' => accented(GRAVE, O),
+        'Ó' => accented(ACUTE, O),
+        'Ô' => accented(CIRCUMFLEX, O),
+        'Õ' => accented(TILDE, O),
+        'Ö' => accented(DIAERESIS, O),
+        '×' => [0b10001, 0b01010, 0b00100, 0b01010, 0b10001],
+        'Ø' => [0b01101, 0b10010, 0b10110, 0b11010, 0b01100],
+        'Ù' => accented(GRAVE, U),
+        'Ú' => accented(ACUTE, U),
+        'Û' => accented(CIRCUMFLEX, U),
+        'Ü' => accented(DIAERESIS, U),
+        'Ý' => accented(ACUTE, Y),
+        'Þ' | 'þ' => [0b10000, 0b11100, 0b10010, 0b11100, 0b10000],
+        'ß' => [0b01100, 0b10010, 0b10100, 0b10010, 0b10100],
+        'à' => accented(GRAVE, LOWER_A),
+        'á' => accented(ACUTE, LOWER_A),
+        'â' => accented(CIRCUMFLEX, LOWER_A),
+        'ã' => accented(TILDE, LOWER_A),
+        'ä' => accented(DIAERESIS, LOWER_A),
+        'å' => [0b01100, 0b01100, 0b10010, 0b10010, 0b01111],
+        'æ' => [0b00000, 0b11110, 0b01011, 0b11110, 0b11011],
+        'ç' => [0b01110, 0b10000, 0b10000, 0b01110, 0b00100],
+        'è' => accented(GRAVE, LOWER_E),
+        'é' => accented(ACUTE, LOWER_E),
+        'ê' => accented(CIRCUMFLEX, LOWER_E),
+        'ë' => accented(DIAERESIS, LOWER_E),
+        'ì' => accented(I_GRAVE, LOWER_I),
+        'í' => accented(I_ACUTE, LOWER_I),
+        'î' => accented(I_CIRCUMFLEX, LOWER_I),
+        'ï' => accented(I_DIAERESIS, LOWER_I),
+        'ð' => [0b01010, 0b00110, 0b01110, 0b10010, 0b01100],
+        'ñ' => accented(TILDE, LOWER_N),
+        'ò' => accented(GRAVE, LOWER_O),
+        'ó' => accented(ACUTE, LOWER_O),
+        'ô' => accented(CIRCUMFLEX, LOWER_O),
+        'õ' => accented(TILDE, LOWER_O),
+        'ö' => accented(DIAERESIS, LOWER_O),
+        '÷' => [0b00100, 0b00000, 0b11111, 0b00000, 0b00100],
+        'ø' => [0b00001, 0b01110, 0b10110, 0b11010, 0b01100],
+        'ù' => accented(GRAVE, LOWER_U),
+        'ú' => accented(ACUTE, LOWER_U),
+        'û' => accented(CIRCUMFLEX, LOWER_U),
+        'ü' => accented(DIAERESIS, LOWER_U),
+        // `y` is 5 columns wide, so its accents need to be moved over a bit.
+        'ý' => accented(ACUTE >> 1, LOWER_Y),
+        'ÿ' => accented(0b01010, LOWER_Y),
+
+        // Other symbols
+        '€' => [0b00111, 0b01000, 0b11110, 0b01000, 0b00111],
+        '←' => [0b00100, 0b01000, 0b11111, 0b01000, 0b00100],
+        '↑' => [0b00100, 0b01110, 0b10101, 0b00100, 0b00100],
+        '→' => [0b00100, 0b00010, 0b11111, 0b00010, 0b00100],
+        '↓' => [0b00100, 0b00100, 0b10101, 0b01110, 0b00100],
+        '♥' => [0b01010, 0b11111, 0b11111, 0b01110, 0b00100],
+        '♪' => [0b00100, 0b00110, 0b00101, 0b11100, 0b11100],
+        '✓' => [0b00000, 0b00001, 0b00010, 0b10100, 0b01000],
+        '✗' => [0b10001, 0b01010, 0b00100, 0b01010, 0b10001],
+
+        _ => return None,
+    })
+}
@@ -0,0 +1,71 @@
+//! Support for drawing onto images with `embedded-graphics`.
+//!
+//! Colours are `Gray8`, where the luma of each pixel is used directly as the LED's value.
+//! `BinaryColor` can be converted into `Gray8` with `Into`, or by using `DrawTargetExt::color_converted` from `embedded-graphics`.
+
+use core::convert::Infallible;
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::OriginDimensions;
+use embedded_graphics_core::geometry::Size;
+use embedded_graphics_core::pixelcolor::Gray8;
+use embedded_graphics_core::pixelcolor::GrayColor;
+use embedded_graphics_core::Pixel;
+
+use super::Canvas;
+use super::Image;
+
+impl OriginDimensions for Image {
+    fn size(&self) -> Size {
+        Size::new(5, 5)
+    }
+}
+
+impl DrawTarget for Image {
+    type Color = Gray8;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            // Anything off the edge of the display is ignored.
+            if (0..5).contains(&point.x) && (0..5).contains(&point.y) {
+                self[point.y as usize][point.x as usize] = color.luma();
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        *self = Image([[color.luma(); 5]; 5]);
+        Ok(())
+    }
+}
+
+impl<const W: usize, const H: usize> OriginDimensions for Canvas<W, H> {
+    fn size(&self) -> Size {
+        Size::new(W as u32, H as u32)
+    }
+}
+
+impl<const W: usize, const H: usize> DrawTarget for Canvas<W, H> {
+    type Color = Gray8;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            self.set_pixel(point.x as isize, point.y as isize, color.luma());
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill(color.luma());
+        Ok(())
+    }
+}
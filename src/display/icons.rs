@@ -0,0 +1,99 @@
+//! The standard set of images from MicroPython's `Image` class.
+
+use super::Image;
+
+// These are written packed like the font, with one bit per LED.
+impl Image {
+    pub const HEART: Self = Self::unpack([0b01010, 0b11111, 0b11111, 0b01110, 0b00100]);
+    pub const HEART_SMALL: Self = Self::unpack([0b00000, 0b01010, 0b01110, 0b00100, 0b00000]);
+    pub const HAPPY: Self = Self::unpack([0b00000, 0b01010, 0b00000, 0b10001, 0b01110]);
+    pub const SMILE: Self = Self::unpack([0b00000, 0b00000, 0b00000, 0b10001, 0b01110]);
+    pub const SAD: Self = Self::unpack([0b00000, 0b01010, 0b00000, 0b01110, 0b10001]);
+    pub const CONFUSED: Self = Self::unpack([0b00000, 0b01010, 0b00000, 0b01010, 0b10101]);
+    pub const ANGRY: Self = Self::unpack([0b10001, 0b01010, 0b00000, 0b11111, 0b10101]);
+    pub const ASLEEP: Self = Self::unpack([0b00000, 0b11011, 0b00000, 0b01110, 0b00000]);
+    pub const SURPRISED: Self = Self::unpack([0b01010, 0b00000, 0b00100, 0b01010, 0b00100]);
+    pub const SILLY: Self = Self::unpack([0b10001, 0b00000, 0b11111, 0b00101, 0b00111]);
+    pub const FABULOUS: Self = Self::unpack([0b11111, 0b11011, 0b00000, 0b01010, 0b01110]);
+    pub const MEH: Self = Self::unpack([0b01010, 0b00000, 0b00010, 0b00100, 0b01000]);
+    pub const YES: Self = Self::unpack([0b00000, 0b00001, 0b00010, 0b10100, 0b01000]);
+    pub const NO: Self = Self::unpack([0b10001, 0b01010, 0b00100, 0b01010, 0b10001]);
+    pub const CLOCK12: Self = Self::unpack([0b00100, 0b00100, 0b00100, 0b00000, 0b00000]);
+    pub const CLOCK11: Self = Self::unpack([0b01000, 0b01000, 0b00100, 0b00000, 0b00000]);
+    pub const CLOCK10: Self = Self::unpack([0b00000, 0b11000, 0b00100, 0b00000, 0b00000]);
+    pub const CLOCK9: Self = Self::unpack([0b00000, 0b00000, 0b11100, 0b00000, 0b00000]);
+    pub const CLOCK8: Self = Self::unpack([0b00000, 0b00000, 0b00100, 0b11000, 0b00000]);
+    pub const CLOCK7: Self = Self::unpack([0b00000, 0b00000, 0b00100, 0b01000, 0b01000]);
+    pub const CLOCK6: Self = Self::unpack([0b00000, 0b00000, 0b00100, 0b00100, 0b00100]);
+    pub const CLOCK5: Self = Self::unpack([0b00000, 0b00000, 0b00100, 0b00010, 0b00010]);
+    pub const CLOCK4: Self = Self::unpack([0b00000, 0b00000, 0b00100, 0b00011, 0b00000]);
+    pub const CLOCK3: Self = Self::unpack([0b00000, 0b00000, 0b00111, 0b00000, 0b00000]);
+    pub const CLOCK2: Self = Self::unpack([0b00000, 0b00011, 0b00100, 0b00000, 0b00000]);
+    pub const CLOCK1: Self = Self::unpack([0b00010, 0b00010, 0b00100, 0b00000, 0b00000]);
+    pub const ARROW_N: Self = Self::unpack([0b00100, 0b01110, 0b10101, 0b00100, 0b00100]);
+    pub const ARROW_NE: Self = Self::unpack([0b00111, 0b00011, 0b00101, 0b01000, 0b10000]);
+    pub const ARROW_E: Self = Self::unpack([0b00100, 0b00010, 0b11111, 0b00010, 0b00100]);
+    pub const ARROW_SE: Self = Self::unpack([0b10000, 0b01000, 0b00101, 0b00011, 0b00111]);
+    pub const ARROW_S: Self = Self::unpack([0b00100, 0b00100, 0b10101, 0b01110, 0b00100]);
+    pub const ARROW_SW: Self = Self::unpack([0b00001, 0b00010, 0b10100, 0b11000, 0b11100]);
+    pub const ARROW_W: Self = Self::unpack([0b00100, 0b01000, 0b11111, 0b01000, 0b00100]);
+    pub const ARROW_NW: Self = Self::unpack([0b11100, 0b11000, 0b10100, 0b00010, 0b00001]);
+    pub const TRIANGLE: Self = Self::unpack([0b00000, 0b00100, 0b01010, 0b11111, 0b00000]);
+    pub const TRIANGLE_LEFT: Self = Self::unpack([0b10000, 0b11000, 0b10100, 0b10010, 0b11111]);
+    pub const CHESSBOARD: Self = Self::unpack([0b01010, 0b10101, 0b01010, 0b10101, 0b01010]);
+    pub const DIAMOND: Self = Self::unpack([0b00100, 0b01010, 0b10001, 0b01010, 0b00100]);
+    pub const DIAMOND_SMALL: Self = Self::unpack([0b00000, 0b00100, 0b01010, 0b00100, 0b00000]);
+    pub const SQUARE: Self = Self::unpack([0b11111, 0b10001, 0b10001, 0b10001, 0b11111]);
+    pub const SQUARE_SMALL: Self = Self::unpack([0b00000, 0b01110, 0b01010, 0b01110, 0b00000]);
+    pub const RABBIT: Self = Self::unpack([0b10100, 0b10100, 0b11110, 0b11010, 0b11110]);
+    pub const COW: Self = Self::unpack([0b10001, 0b10001, 0b11111, 0b01110, 0b00100]);
+    pub const MUSIC_CROTCHET: Self = Self::unpack([0b00100, 0b00100, 0b00100, 0b11100, 0b11100]);
+    pub const MUSIC_QUAVER: Self = Self::unpack([0b00100, 0b00110, 0b00101, 0b11100, 0b11100]);
+    pub const MUSIC_QUAVERS: Self = Self::unpack([0b01111, 0b01001, 0b01001, 0b11011, 0b11011]);
+    pub const PITCHFORK: Self = Self::unpack([0b10101, 0b10101, 0b11111, 0b00100, 0b00100]);
+    pub const XMAS: Self = Self::unpack([0b00100, 0b01110, 0b00100, 0b01110, 0b11111]);
+    pub const PACMAN: Self = Self::unpack([0b01111, 0b11010, 0b11100, 0b11110, 0b01111]);
+    pub const TARGET: Self = Self::unpack([0b00100, 0b01110, 0b11011, 0b01110, 0b00100]);
+    pub const TSHIRT: Self = Self::unpack([0b11011, 0b11111, 0b01110, 0b01110, 0b01110]);
+    pub const ROLLERSKATE: Self = Self::unpack([0b00011, 0b00011, 0b11111, 0b11111, 0b01010]);
+    pub const DUCK: Self = Self::unpack([0b01100, 0b11100, 0b01111, 0b01110, 0b00000]);
+    pub const HOUSE: Self = Self::unpack([0b00100, 0b01110, 0b11111, 0b01110, 0b01010]);
+    pub const TORTOISE: Self = Self::unpack([0b00000, 0b01110, 0b11111, 0b01010, 0b00000]);
+    pub const BUTTERFLY: Self = Self::unpack([0b11011, 0b11111, 0b00100, 0b11111, 0b11011]);
+    pub const STICKFIGURE: Self = Self::unpack([0b00100, 0b11111, 0b00100, 0b01010, 0b10001]);
+    pub const GHOST: Self = Self::unpack([0b11111, 0b10101, 0b11111, 0b11111, 0b10101]);
+    pub const SWORD: Self = Self::unpack([0b00100, 0b00100, 0b00100, 0b01110, 0b00100]);
+    pub const GIRAFFE: Self = Self::unpack([0b11000, 0b01000, 0b01000, 0b01110, 0b01010]);
+    pub const SKULL: Self = Self::unpack([0b01110, 0b10101, 0b11111, 0b01110, 0b01110]);
+    pub const UMBRELLA: Self = Self::unpack([0b01110, 0b11111, 0b00100, 0b10100, 0b01100]);
+    pub const SNAKE: Self = Self::unpack([0b11000, 0b11011, 0b01010, 0b01110, 0b00000]);
+    pub const SCISSORS: Self = Self::unpack([0b11001, 0b11010, 0b00100, 0b11010, 0b11001]);
+
+    /// The clock faces in order, starting from 12 o'clock.
+    pub const ALL_CLOCKS: [Self; 12] = [
+        Self::CLOCK12,
+        Self::CLOCK1,
+        Self::CLOCK2,
+        Self::CLOCK3,
+        Self::CLOCK4,
+        Self::CLOCK5,
+        Self::CLOCK6,
+        Self::CLOCK7,
+        Self::CLOCK8,
+        Self::CLOCK9,
+        Self::CLOCK10,
+        Self::CLOCK11,
+    ];
+
+    /// The arrows in clockwise order, starting from north.
+    pub const ALL_ARROWS: [Self; 8] = [
+        Self::ARROW_N,
+        Self::ARROW_NE,
+        Self::ARROW_E,
+        Self::ARROW_SE,
+        Self::ARROW_S,
+        Self::ARROW_SW,
+        Self::ARROW_W,
+        Self::ARROW_NW,
+    ];
+}
@@ -0,0 +1,237 @@
+//! How images map onto the LED matrix's hardware rows and columns, and how long each LED is lit for.
+
+// None of this is used on the host outside of tests, since there's no display to drive.
+#![cfg_attr(not(target_os = "none"), allow(dead_code))]
+
+use super::Image;
+
+#[cfg(not(v2))]
+pub const HW_ROWS: usize = 3;
+#[cfg(not(v2))]
+pub const HW_COLS: usize = 9;
+
+#[cfg(v2)]
+pub const HW_ROWS: usize = 5;
+#[cfg(v2)]
+pub const HW_COLS: usize = 5;
+
+impl Image {
+    #[doc(hidden)]
+    pub fn hw_rows(&self) -> [[u8; HW_COLS]; HW_ROWS] {
+        #[cfg(not(v2))]
+        return [
+            [
+                self[0][0], self[0][2], self[0][4], self[3][4], self[3][3], self[3][2], self[3][1],
+                self[3][0], self[2][1],
+            ],
+            [
+                self[2][4], self[2][0], self[2][2], self[0][1], self[0][3], self[4][3], self[4][1],
+                0, 0,
+            ],
+            [
+                self[4][2], self[4][4], self[4][0], self[1][0], self[1][1], self[1][2], self[1][3],
+                self[1][4], self[2][3],
+            ],
+        ];
+        #[cfg(v2)]
+        self.0
+    }
+
+    /// Returns which of the hardware rows the LED at `(x, y)` is wired up to; see `hw_rows`.
+    pub(super) fn hw_row_of(x: usize, y: usize) -> usize {
+        #[cfg(not(v2))]
+        return [
+            [0, 1, 0, 1, 0],
+            [2, 2, 2, 2, 2],
+            [1, 0, 1, 2, 1],
+            [0, 0, 0, 0, 0],
+            [2, 1, 2, 1, 2],
+        ][y][x];
+        #[cfg(v2)]
+        {
+            let _ = x;
+            y
+        }
+    }
+
+    /// Works out when each LED needs to be turned off, using a table from `level_ticks`.
+    #[doc(hidden)]
+    pub fn steps(&self, level_ticks: &[u16; 256]) -> [[(u16, usize); HW_COLS]; HW_ROWS] {
+        let hw_rows = self.hw_rows();
+
+        let mut out = [[(0, 0); HW_COLS]; HW_ROWS];
+        for (row, hw_row) in out.iter_mut().zip(hw_rows.iter()) {
+            *row = row_steps(hw_row, level_ticks);
+        }
+        out
+    }
+}
+
+/// Works out how long an LED with each value should be on for, after mapping it through `curve` and scaling it by `brightness`.
+///
+/// This only needs redoing when the brightness settings change, which saves a division per LED every time an image is shown;
+/// the Cortex-M0 in the v1 doesn't have a hardware divider.
+pub fn level_ticks(curve: &[u8; 256], brightness: u8, ticks_per_row: u16) -> [u16; 256] {
+    let mut out = [0; 256];
+    for (ticks, &level) in out.iter_mut().zip(curve.iter()) {
+        let value = level as u32 * brightness as u32;
+        *ticks = (ticks_per_row as u32 * value / (255 * 255)) as u16;
+    }
+    out
+}
+
+/// Works out the steps for a single hardware row; see `Image::steps`.
+pub fn row_steps(hw_row: &[u8; HW_COLS], level_ticks: &[u16; 256]) -> [(u16, usize); HW_COLS] {
+    let mut out = [(0, 0); HW_COLS];
+    // Insertion sort the columns as they're added, which is cheap with only up to 9 of them.
+    // It's stable, so columns with the same time stay in column order.
+    for (j, &value) in hw_row.iter().enumerate() {
+        let time = level_ticks[value as usize];
+        let mut k = j;
+        while k > 0 && out[k - 1].0 > time {
+            out[k] = out[k - 1];
+            k -= 1;
+        }
+        out[k] = (time, j);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::GAMMA;
+    use crate::display::LINEAR;
+
+    /// The original way of working out a row's steps, with a division per LED followed by an unstable sort.
+    fn reference_row_steps(
+        hw_row: &[u8; HW_COLS],
+        curve: &[u8; 256],
+        brightness: u8,
+        ticks_per_row: u16,
+    ) -> [(u16, usize); HW_COLS] {
+        let mut out = [(0, 0); HW_COLS];
+        for (j, col) in out.iter_mut().enumerate() {
+            let value = curve[hw_row[j] as usize] as u32 * brightness as u32;
+            *col = ((ticks_per_row as u32 * value / (255 * 255)) as u16, j)
+        }
+
+        out.sort_unstable_by_key(|&(time, _)| time);
+        out
+    }
+
+    /// A tiny xorshift generator, so that lots of images can be tried without pulling in `rand`.
+    struct Rng(u32);
+
+    impl Rng {
+        fn next(&mut self) -> u8 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            (self.0 >> 24) as u8
+        }
+
+        /// Returns a random LED value, often picked from only a few so that plenty of columns end up with the same time.
+        fn value(&mut self) -> u8 {
+            let value = self.next();
+            if value & 1 == 0 {
+                value & 0b1100_0000
+            } else {
+                value
+            }
+        }
+    }
+
+    /// Runs `f` with every combination of curve, brightness and row length worth testing, along with the matching `level_ticks`.
+    fn for_each_setting(mut f: impl FnMut(&[u8; 256], u8, u16, &[u16; 256])) {
+        // A curve which isn't monotonic, so that sorting by value wouldn't be enough.
+        let mut reversed = [0; 256];
+        for (i, level) in reversed.iter_mut().enumerate() {
+            *level = 255 - i as u8;
+        }
+
+        for curve in [&LINEAR, &GAMMA, &reversed] {
+            for brightness in [0, 1, 2, 100, 128, 254, 255] {
+                for ticks_per_row in [255, 5555, 6666, 16383] {
+                    let level_ticks = level_ticks(curve, brightness, ticks_per_row);
+                    f(curve, brightness, ticks_per_row, &level_ticks);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn row_steps_match_reference() {
+        let mut rng = Rng(0x1234_5678);
+        for_each_setting(|curve, brightness, ticks_per_row, level_ticks| {
+            for _ in 0..200 {
+                let mut hw_row = [0; HW_COLS];
+                for value in hw_row.iter_mut() {
+                    *value = rng.value();
+                }
+
+                let mut expected = reference_row_steps(&hw_row, curve, brightness, ticks_per_row);
+                // The reference's sort isn't stable, so put columns with the same time back in column order,
+                // which is what `row_steps` guarantees.
+                expected.sort_unstable();
+                assert_eq!(
+                    row_steps(&hw_row, level_ticks),
+                    expected,
+                    "row {:?} at brightness {} with {} ticks per row",
+                    hw_row,
+                    brightness,
+                    ticks_per_row
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn steps_match_reference() {
+        let mut rng = Rng(0x8765_4321);
+        for_each_setting(|curve, brightness, ticks_per_row, level_ticks| {
+            for _ in 0..20 {
+                let mut image = Image::BLANK;
+                for value in image.iter_mut().flatten() {
+                    *value = rng.value();
+                }
+
+                let steps = image.steps(level_ticks);
+                for (row, hw_row) in steps.iter().zip(image.hw_rows().iter()) {
+                    let mut expected =
+                        reference_row_steps(hw_row, curve, brightness, ticks_per_row);
+                    expected.sort_unstable();
+                    assert_eq!(*row, expected, "{:?}", image);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn hw_row_of_matches_hw_rows() {
+        for y in 0..5 {
+            for x in 0..5 {
+                let mut image = Image::BLANK;
+                image[y][x] = 255;
+                let hw_rows = image.hw_rows();
+                let lit: usize = hw_rows
+                    .iter()
+                    .flatten()
+                    .filter(|&&value| value != 0)
+                    .count();
+                assert_eq!(
+                    lit, 1,
+                    "({}, {}) should be wired up to exactly one LED",
+                    x, y
+                );
+                assert!(
+                    hw_rows[Image::hw_row_of(x, y)].contains(&255),
+                    "({}, {}) isn't in hardware row {}",
+                    x,
+                    y,
+                    Image::hw_row_of(x, y)
+                );
+            }
+        }
+    }
+}
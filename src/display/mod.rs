@@ -0,0 +1,149 @@
+use core::ops::Deref;
+use core::ops::DerefMut;
+
+// Everything that touches the hardware or embassy's timers is only built for the target;
+// the rest (images, fonts and the timing maths) is also built on the host, for unit testing.
+#[cfg(target_os = "none")]
+mod animation;
+mod canvas;
+#[cfg(target_os = "none")]
+mod driver;
+pub mod font;
+#[cfg(feature = "embedded-graphics-core")]
+mod graphics;
+mod icons;
+// This is only public so that `examples/bench_show.rs` can time the real thing.
+#[doc(hidden)]
+pub mod matrix;
+mod parse;
+#[cfg(target_os = "none")]
+mod scroll;
+mod transform;
+
+#[cfg(target_os = "none")]
+pub use animation::Animation;
+#[cfg(target_os = "none")]
+pub use animation::Frame;
+#[cfg(target_os = "none")]
+pub use animation::Repeat;
+pub use canvas::Canvas;
+#[cfg(target_os = "none")]
+pub use driver::Display;
+#[cfg(target_os = "none")]
+pub use driver::DisplayConfig;
+#[cfg(target_os = "none")]
+pub use driver::DisplayTimer;
+#[cfg(target_os = "none")]
+pub use driver::LightSensorAdc;
+#[cfg(target_os = "none")]
+pub use driver::Pins;
+pub use parse::ParseError;
+#[cfg(target_os = "none")]
+pub use scroll::ScrollDirection;
+#[cfg(target_os = "none")]
+pub use scroll::ScrollOptions;
+
+/// A brightness curve which maps LED values directly to on-time.
+///
+/// LEDs look much brighter than their on-time suggests at low levels, so with this the upper levels are hard to tell apart.
+pub static LINEAR: [u8; 256] = linear();
+
+/// A brightness curve with a gamma of 2.2, so that LED values look evenly spaced.
+///
+/// Every non-zero value maps to at least 1, so that dim LEDs don't disappear entirely.
+#[rustfmt::skip]
+pub static GAMMA: [u8; 256] = [
+    0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2,
+    3, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 6, 6, 6,
+    6, 7, 7, 7, 8, 8, 8, 9, 9, 9, 10, 10, 11, 11, 11, 12,
+    12, 13, 13, 13, 14, 14, 15, 15, 16, 16, 17, 17, 18, 18, 19, 19,
+    20, 20, 21, 22, 22, 23, 23, 24, 25, 25, 26, 26, 27, 28, 28, 29,
+    30, 30, 31, 32, 33, 33, 34, 35, 35, 36, 37, 38, 39, 39, 40, 41,
+    42, 43, 43, 44, 45, 46, 47, 48, 49, 49, 50, 51, 52, 53, 54, 55,
+    56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71,
+    73, 74, 75, 76, 77, 78, 79, 81, 82, 83, 84, 85, 87, 88, 89, 90,
+    91, 93, 94, 95, 97, 98, 99, 100, 102, 103, 105, 106, 107, 109, 110, 111,
+    113, 114, 116, 117, 119, 120, 121, 123, 124, 126, 127, 129, 130, 132, 133, 135,
+    137, 138, 140, 141, 143, 145, 146, 148, 149, 151, 153, 154, 156, 158, 159, 161,
+    163, 165, 166, 168, 170, 172, 173, 175, 177, 179, 181, 182, 184, 186, 188, 190,
+    192, 194, 196, 197, 199, 201, 203, 205, 207, 209, 211, 213, 215, 217, 219, 221,
+    223, 225, 227, 229, 231, 234, 236, 238, 240, 242, 244, 246, 248, 251, 253, 255,
+];
+
+const fn linear() -> [u8; 256] {
+    let mut curve = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        curve[i] = i as u8;
+        i += 1;
+    }
+    curve
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image(pub [[u8; 5]; 5]);
+
+impl Deref for Image {
+    type Target = [[u8; 5]; 5];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Image {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Image {
+    pub const BLANK: Self = Image([[0; 5]; 5]);
+
+    fn column_non_blank(&self, i: usize) -> bool {
+        for row in self.0.iter() {
+            if row[i] != 0 {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Works out the range of columns to show for a glyph from the font when they're placed side by side.
+    ///
+    /// This performs 'kerning' by skipping a few blank columns on the left and right.
+    /// It doesn't skip all of them so that spaces still exist.
+    fn kerned_columns(&self) -> (usize, usize) {
+        let start = if self.column_non_blank(0) { 0 } else { 1 };
+        let end = if self.column_non_blank(4) {
+            5
+        } else if self.column_non_blank(3) {
+            4
+        } else {
+            3
+        };
+        (start, end)
+    }
+
+    /// Unpack a 'compressed' image, where each row is a u8 with each bit representing an LED being on or off.
+    /// Used to reduce binary size taken by font.
+    const fn unpack(data: [u8; 5]) -> Self {
+        const fn unpack(row: u8) -> [u8; 5] {
+            [
+                if row & 0b10000 != 0 { 255 } else { 0 },
+                if row & 0b01000 != 0 { 255 } else { 0 },
+                if row & 0b00100 != 0 { 255 } else { 0 },
+                if row & 0b00010 != 0 { 255 } else { 0 },
+                if row & 0b00001 != 0 { 255 } else { 0 },
+            ]
+        }
+        Self([
+            unpack(data[0]),
+            unpack(data[1]),
+            unpack(data[2]),
+            unpack(data[3]),
+            unpack(data[4]),
+        ])
+    }
+}
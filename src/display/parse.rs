@@ -0,0 +1,206 @@
+//! Converting images to and from MicroPython's `"09090:99999:..."` strings.
+
+use core::fmt;
+use core::str;
+use core::str::FromStr;
+
+use defmt::Format;
+
+use super::Image;
+
+/// The reason a string couldn't be parsed as an `Image`.
+#[derive(Clone, Copy, Debug, Format, PartialEq, Eq)]
+pub enum ParseError {
+    /// The byte at `index` wasn't a digit or a separator.
+    InvalidChar {
+        index: usize,
+    },
+    /// The row at index `row` didn't have exactly 5 digits.
+    WrongRowLength {
+        row: usize,
+    },
+    TooFewRows,
+    TooManyRows,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidChar { index } => write!(f, "invalid character at index {}", index),
+            Self::WrongRowLength { row } => write!(f, "row {} doesn't have 5 digits", row),
+            Self::TooFewRows => f.write_str("fewer than 5 rows"),
+            Self::TooManyRows => f.write_str("more than 5 rows"),
+        }
+    }
+}
+
+/// The length of an image's string form: 5 digits per row, plus the separators between them.
+const STRING_LEN: usize = 5 * 5 + 4;
+
+impl Image {
+    /// Parses an image from a string of brightnesses from 0 to 9, like MicroPython's `Image("09090:99999:99999:09990:00900")`.
+    ///
+    /// Rows can be separated by either `:` or newlines, and there can be a separator after the last row.
+    ///
+    /// This is a `const fn`, but the `image!` macro is more convenient for images known at compile time.
+    pub const fn parse(s: &str) -> Result<Self, ParseError> {
+        let bytes = s.as_bytes();
+        let mut image = [[0; 5]; 5];
+        let mut row = 0;
+        let mut col = 0;
+
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                digit @ b'0'..=b'9' => {
+                    if row == 5 {
+                        return Err(ParseError::TooManyRows);
+                    }
+                    if col == 5 {
+                        return Err(ParseError::WrongRowLength { row });
+                    }
+                    image[row][col] = ((digit - b'0') as u32 * 255 / 9) as u8;
+                    col += 1;
+                }
+                b':' | b'\n' => {
+                    if col != 5 {
+                        return Err(ParseError::WrongRowLength { row });
+                    }
+                    row += 1;
+                    col = 0;
+                }
+                // Allow Windows line endings.
+                b'\r' => {}
+                _ => return Err(ParseError::InvalidChar { index: i }),
+            }
+            i += 1;
+        }
+
+        // The last row doesn't need a separator after it.
+        if col == 5 {
+            row += 1;
+        } else if col != 0 {
+            return Err(ParseError::WrongRowLength { row });
+        }
+
+        if row < 5 {
+            Err(ParseError::TooFewRows)
+        } else {
+            Ok(Self(image))
+        }
+    }
+
+    /// Writes out the image in the same format `parse` accepts, with each LED rounded to the nearest digit.
+    fn to_digits(&self) -> [u8; STRING_LEN] {
+        let mut out = [b':'; STRING_LEN];
+        for (i, row) in self.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                out[i * 6 + j] = b'0' + ((value as u32 * 9 + 127) / 255) as u8;
+            }
+        }
+        out
+    }
+}
+
+impl FromStr for Image {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(str::from_utf8(&self.to_digits()).unwrap())
+    }
+}
+
+impl Format for Image {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{=str}", str::from_utf8(&self.to_digits()).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEART: &str = "09090:99999:99999:09990:00900";
+
+    #[test]
+    fn round_trip() {
+        let image = Image::parse(HEART).unwrap();
+        assert_eq!(image.to_string(), HEART);
+        assert_eq!(image.to_string().parse::<Image>(), Ok(image.clone()));
+
+        // Every digit should survive being scaled up to a brightness and back.
+        let digits = "01234:56789:98765:43210:00000";
+        assert_eq!(Image::parse(digits).unwrap().to_string(), digits);
+        assert_eq!(
+            Image([[255; 5]; 5]).to_string(),
+            "99999:99999:99999:99999:99999"
+        );
+    }
+
+    #[test]
+    fn separators() {
+        let image = Image::parse(HEART);
+        assert_eq!(Image::parse("09090\n99999\n99999\n09990\n00900"), image);
+        assert_eq!(Image::parse("09090:99999\n99999:09990\n00900"), image);
+        assert_eq!(Image::parse("09090:99999:99999:09990:00900:"), image);
+        assert_eq!(Image::parse("09090\n99999\n99999\n09990\n00900\n"), image);
+        assert_eq!(
+            Image::parse("09090\r\n99999\r\n99999\r\n09990\r\n00900\r\n"),
+            image
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Image::parse("09090:99999:99a99:09990:00900"),
+            Err(ParseError::InvalidChar { index: 14 })
+        );
+        assert_eq!(
+            Image::parse("09090 99999:99999:09990:00900"),
+            Err(ParseError::InvalidChar { index: 5 })
+        );
+        assert_eq!(
+            Image::parse("09090:9999:99999:09990:00900"),
+            Err(ParseError::WrongRowLength { row: 1 })
+        );
+        assert_eq!(
+            Image::parse("09090:99999:99999:09990:009000"),
+            Err(ParseError::WrongRowLength { row: 4 })
+        );
+        assert_eq!(
+            Image::parse("09090:99999:99999:09990:0090"),
+            Err(ParseError::WrongRowLength { row: 4 })
+        );
+        assert_eq!(
+            Image::parse("09090::99999:99999:09990"),
+            Err(ParseError::WrongRowLength { row: 1 })
+        );
+        assert_eq!(Image::parse(""), Err(ParseError::TooFewRows));
+        assert_eq!(
+            Image::parse("09090:99999:99999:09990"),
+            Err(ParseError::TooFewRows)
+        );
+        assert_eq!(
+            Image::parse("09090:99999:99999:09990:00900:00000"),
+            Err(ParseError::TooManyRows)
+        );
+    }
+
+    #[test]
+    fn image_macro() {
+        const IMAGE: Image = crate::image!("09090:99999:99999:09990:00900");
+        assert_eq!(IMAGE, Image::parse(HEART).unwrap());
+        assert_eq!(IMAGE[0], [0, 255, 0, 255, 0]);
+        assert_eq!(
+            crate::image!("00000\n00000\n00900\n00000\n00000")[2][2],
+            255
+        );
+    }
+}
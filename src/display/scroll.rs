@@ -0,0 +1,381 @@
+//! Scrolling text across the display.
+
+use core::fmt;
+use core::iter::Peekable;
+use core::str::Chars;
+
+use defmt::Format;
+use embassy::time::Duration;
+use embassy::time::Instant;
+
+use super::Image;
+
+/// The direction text moves in while scrolling.
+#[derive(Clone, Copy, Debug, Format, PartialEq, Eq)]
+pub enum ScrollDirection {
+    /// Text comes in from the right and moves to the left, like a normal marquee.
+    Left,
+    /// Text comes in from the left and moves to the right.
+    Right,
+    /// Text comes in from the bottom and moves upwards.
+    Up,
+    /// Text comes in from the top and moves downwards.
+    Down,
+}
+
+impl ScrollDirection {
+    /// Whether the text has to be fed in from its last character, so that it still reads the right way around once it's on the display.
+    pub(super) fn reversed(self) -> bool {
+        matches!(self, Self::Right | Self::Down)
+    }
+
+    fn is_horizontal(self) -> bool {
+        matches!(self, Self::Left | Self::Right)
+    }
+
+    /// Returns the `i`th column or row of `glyph`, whichever one lines up with the edge text comes in from.
+    fn slice(self, glyph: &Image, i: usize) -> [u8; 5] {
+        if self.is_horizontal() {
+            [
+                glyph[0][i],
+                glyph[1][i],
+                glyph[2][i],
+                glyph[3][i],
+                glyph[4][i],
+            ]
+        } else {
+            glyph[i]
+        }
+    }
+
+    /// Moves everything on `image` along by one, and puts `slice` in the gap that leaves.
+    fn push(self, image: &mut Image, slice: [u8; 5]) {
+        match self {
+            Self::Left => {
+                for (row, value) in image.iter_mut().zip(slice) {
+                    row.copy_within(1.., 0);
+                    row[4] = value;
+                }
+            }
+            Self::Right => {
+                for (row, value) in image.iter_mut().zip(slice) {
+                    row.copy_within(..4, 1);
+                    row[0] = value;
+                }
+            }
+            Self::Up => {
+                image.copy_within(1.., 0);
+                image[4] = slice;
+            }
+            Self::Down => {
+                image.copy_within(..4, 1);
+                image[0] = slice;
+            }
+        }
+    }
+}
+
+/// Settings for how text is scrolled across the display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScrollOptions {
+    /// How long to wait before moving the text along by one more column (or row).
+    pub delay: Duration,
+    pub direction: ScrollDirection,
+    /// The number of blank columns (or rows) between each character.
+    pub spacing: usize,
+    /// Whether to skip blank columns at the edges of characters, so that narrow ones don't leave big gaps.
+    ///
+    /// This only affects scrolling left or right.
+    pub kerning: bool,
+    /// Whether to start with a blank display and scroll the text in, rather than starting with it already filling the display.
+    pub leading_blank: bool,
+    /// Whether to keep scrolling until the text has gone off the display, rather than stopping once the last character is fully on.
+    pub trailing_blank: bool,
+}
+
+impl ScrollOptions {
+    pub const DEFAULT: Self = Self {
+        delay: Duration::from_millis(150),
+        direction: ScrollDirection::Left,
+        spacing: 1,
+        kerning: true,
+        leading_blank: true,
+        trailing_blank: true,
+    };
+}
+
+impl Default for ScrollOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+enum Phase {
+    /// Moving in the columns (or rows) `next..end` of a character.
+    Glyph {
+        glyph: Image,
+        start: usize,
+        next: usize,
+        end: usize,
+    },
+    /// Moving in blank columns between characters.
+    Gap(usize),
+    /// Moving in blank columns to let the last character scroll away.
+    Trailing(usize),
+}
+
+/// Works out each image to show while scrolling some text, one column (or row) at a time.
+///
+/// If `options.direction` is `reversed`, `chars` has to be the characters of the text in reverse order.
+pub(super) struct Scroller<I: Iterator<Item = char>> {
+    chars: Peekable<I>,
+    options: ScrollOptions,
+    image: Image,
+    phase: Phase,
+}
+
+impl<I: Iterator<Item = char>> Scroller<I> {
+    pub(super) fn new(chars: I, options: ScrollOptions) -> Self {
+        let mut scroller = Self {
+            chars: chars.peekable(),
+            options,
+            image: Image::BLANK,
+            phase: Phase::Gap(0),
+        };
+
+        if !scroller.options.leading_blank {
+            // Skip ahead to the first image where the display's been filled up.
+            for _ in 0..4 {
+                scroller.step();
+            }
+        }
+
+        scroller
+    }
+
+    pub(super) fn options(&self) -> &ScrollOptions {
+        &self.options
+    }
+
+    /// Scrolls one more column onto the display, returning the new image, or `None` once the text has finished scrolling.
+    pub(super) fn step(&mut self) -> Option<Image> {
+        let direction = self.options.direction;
+        loop {
+            match &mut self.phase {
+                Phase::Glyph {
+                    glyph,
+                    start,
+                    next,
+                    end,
+                } if *next < *end => {
+                    let i = if direction.reversed() {
+                        *start + *end - 1 - *next
+                    } else {
+                        *next
+                    };
+                    *next += 1;
+
+                    direction.push(&mut self.image, direction.slice(glyph, i));
+                    return Some(self.image.clone());
+                }
+                Phase::Glyph { .. } => {
+                    self.phase = if self.chars.peek().is_some() {
+                        Phase::Gap(self.options.spacing)
+                    } else if self.options.trailing_blank {
+                        Phase::Trailing(5)
+                    } else {
+                        Phase::Trailing(0)
+                    }
+                }
+                Phase::Gap(n) | Phase::Trailing(n) if *n > 0 => {
+                    *n -= 1;
+                    direction.push(&mut self.image, [0; 5]);
+                    return Some(self.image.clone());
+                }
+                Phase::Gap(_) => {
+                    self.phase = match self.chars.next() {
+                        Some(char) => {
+                            let glyph = Image::from(char);
+
+                            let (start, end) = if self.options.kerning && direction.is_horizontal()
+                            {
+                                glyph.kerned_columns()
+                            } else {
+                                (0, 5)
+                            };
+
+                            Phase::Glyph {
+                                glyph,
+                                start,
+                                next: start,
+                                end,
+                            }
+                        }
+                        // There wasn't any text in the first place.
+                        None => Phase::Trailing(0),
+                    }
+                }
+                Phase::Trailing(_) => return None,
+            }
+        }
+    }
+}
+
+/// The characters of some formatted text, found without allocating by formatting it again up to the character we want each time.
+pub(super) struct FmtChars<'a> {
+    args: fmt::Arguments<'a>,
+    /// The index of the next character to return from the front.
+    front: usize,
+    /// The index after the next character to return from the back.
+    back: usize,
+}
+
+impl<'a> FmtChars<'a> {
+    pub(super) fn new(args: fmt::Arguments<'a>) -> Self {
+        let len = Self::nth(args, usize::MAX).1;
+        Self {
+            args,
+            front: 0,
+            back: len,
+        }
+    }
+
+    /// Formats `args` until reaching the `n`th character, returning it if there was one and how many characters were formatted.
+    fn nth(args: fmt::Arguments<'_>, n: usize) -> (Option<char>, usize) {
+        struct Nth {
+            n: usize,
+            count: usize,
+            found: Option<char>,
+        }
+
+        impl fmt::Write for Nth {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                for char in s.chars() {
+                    if self.count == self.n {
+                        self.found = Some(char);
+                        // Stop formatting, since we've got what we came for.
+                        return Err(fmt::Error);
+                    }
+                    self.count += 1;
+                }
+                Ok(())
+            }
+        }
+
+        let mut nth = Nth {
+            n,
+            count: 0,
+            found: None,
+        };
+        // This only fails when we stop it early.
+        let _ = fmt::write(&mut nth, args);
+        (nth.found, nth.count)
+    }
+}
+
+impl Iterator for FmtChars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if self.front < self.back {
+            self.front += 1;
+            Self::nth(self.args, self.front - 1).0
+        } else {
+            None
+        }
+    }
+}
+
+impl DoubleEndedIterator for FmtChars<'_> {
+    fn next_back(&mut self) -> Option<char> {
+        if self.front < self.back {
+            self.back -= 1;
+            Self::nth(self.args, self.back).0
+        } else {
+            None
+        }
+    }
+}
+
+/// The characters of some `'static` text, in reverse order if `reversed` is set.
+///
+/// This lets `ScrollPlayer` hold either order in the same type.
+pub(super) struct TextChars {
+    chars: Chars<'static>,
+    reversed: bool,
+}
+
+impl TextChars {
+    fn new(text: &'static str, direction: ScrollDirection) -> Self {
+        Self {
+            chars: text.chars(),
+            reversed: direction.reversed(),
+        }
+    }
+}
+
+impl Iterator for TextChars {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if self.reversed {
+            self.chars.next_back()
+        } else {
+            self.chars.next()
+        }
+    }
+}
+
+/// Scrolls some text in the background, advanced from the display's interrupt at the start of each frame.
+pub(super) struct ScrollPlayer {
+    text: &'static str,
+    scroller: Scroller<TextChars>,
+    /// Whether to start scrolling the text again once it's finished.
+    looping: bool,
+    /// When to scroll the next column in.
+    deadline: Instant,
+}
+
+impl ScrollPlayer {
+    /// Starts scrolling `text`, returning the image to show first.
+    pub(super) fn start(
+        text: &'static str,
+        looping: bool,
+        options: ScrollOptions,
+        now: Instant,
+    ) -> (Self, Image) {
+        let mut scroller = Scroller::new(TextChars::new(text, options.direction), options);
+        let image = scroller.step().unwrap_or(Image::BLANK);
+        let player = Self {
+            text,
+            scroller,
+            looping,
+            deadline: now + options.delay,
+        };
+        (player, image)
+    }
+
+    /// Scrolls in however many columns are due by `now`.
+    ///
+    /// Returns the new image to show if it's changed, and whether the text is still scrolling.
+    pub(super) fn advance(&mut self, now: Instant) -> (Option<Image>, bool) {
+        let options = *self.scroller.options();
+        let mut image = None;
+        while now >= self.deadline {
+            match self.scroller.step() {
+                Some(next) => {
+                    image = Some(next);
+                    // Don't let a zero delay loop forever.
+                    self.deadline += options.delay.max(Duration::from_ticks(1));
+                }
+                // Empty text would never produce any steps, so don't let it loop forever.
+                None if self.looping && !self.text.is_empty() => {
+                    self.scroller =
+                        Scroller::new(TextChars::new(self.text, options.direction), options)
+                }
+                None => return (image, false),
+            }
+        }
+        (image, true)
+    }
+}
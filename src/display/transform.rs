@@ -0,0 +1,340 @@
+//! Operations for building new images out of existing ones.
+//!
+//! All of these return a new image rather than modifying the existing one, the same as MicroPython's `Image` methods.
+
+use super::Image;
+
+impl Image {
+    /// Builds an image by calling `f` with the `(x, y)` coordinates of each LED.
+    fn from_fn(mut f: impl FnMut(usize, usize) -> u8) -> Self {
+        let mut image = Self::BLANK;
+        for (y, row) in image.iter_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = f(x, y);
+            }
+        }
+        image
+    }
+
+    /// Returns the value at `(x, y)`, or 0 if it's off the edge of the image.
+    fn get_or_blank(&self, x: isize, y: isize) -> u8 {
+        if (0..5).contains(&x) && (0..5).contains(&y) {
+            self[y as usize][x as usize]
+        } else {
+            0
+        }
+    }
+
+    /// Moves the image `dx` LEDs to the right and `dy` LEDs down, filling in the gaps left behind with blank LEDs.
+    ///
+    /// Negative values move it left or up instead.
+    pub fn shift(&self, dx: isize, dy: isize) -> Self {
+        Self::from_fn(|x, y| {
+            self.get_or_blank(
+                (x as isize).saturating_sub(dx),
+                (y as isize).saturating_sub(dy),
+            )
+        })
+    }
+
+    /// Moves the image `dx` LEDs to the right and `dy` LEDs down, with anything which goes off one edge coming back in on the other.
+    ///
+    /// Negative values move it left or up instead.
+    pub fn wrapping_shift(&self, dx: isize, dy: isize) -> Self {
+        Self::from_fn(|x, y| {
+            let x = (x as isize - dx.rem_euclid(5)).rem_euclid(5) as usize;
+            let y = (y as isize - dy.rem_euclid(5)).rem_euclid(5) as usize;
+            self[y][x]
+        })
+    }
+
+    pub fn shift_left(&self, n: usize) -> Self {
+        self.shift(-(shift_distance(n)), 0)
+    }
+
+    pub fn shift_right(&self, n: usize) -> Self {
+        self.shift(shift_distance(n), 0)
+    }
+
+    pub fn shift_up(&self, n: usize) -> Self {
+        self.shift(0, -(shift_distance(n)))
+    }
+
+    pub fn shift_down(&self, n: usize) -> Self {
+        self.shift(0, shift_distance(n))
+    }
+
+    /// Rotates the image a quarter turn clockwise.
+    pub fn rotate_90(&self) -> Self {
+        Self::from_fn(|x, y| self[4 - x][y])
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        Self::from_fn(|x, y| self[4 - y][4 - x])
+    }
+
+    /// Rotates the image a quarter turn anticlockwise.
+    pub fn rotate_270(&self) -> Self {
+        Self::from_fn(|x, y| self[x][4 - y])
+    }
+
+    /// Mirrors the image left-to-right.
+    pub fn flip_horizontal(&self) -> Self {
+        Self::from_fn(|x, y| self[y][4 - x])
+    }
+
+    /// Mirrors the image top-to-bottom.
+    pub fn flip_vertical(&self) -> Self {
+        Self::from_fn(|x, y| self[4 - y][x])
+    }
+
+    /// Swaps bright and dark LEDs.
+    pub fn invert(&self) -> Self {
+        Self::from_fn(|x, y| 255 - self[y][x])
+    }
+
+    /// Returns the `width` x `height` region of the image with its top-left corner at `(x, y)`, moved to the top-left of a blank image.
+    ///
+    /// Any part of the region which is off the edge of the image is blank.
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self::from_fn(|i, j| {
+            // Saturating is enough here, since anything that would overflow is way off the edge anyway.
+            let (src_x, src_y) = (x.saturating_add(i), y.saturating_add(j));
+            if i < width && j < height && src_x < 5 && src_y < 5 {
+                self[src_y][src_x]
+            } else {
+                0
+            }
+        })
+    }
+
+    /// Copies the `width` x `height` region at the top-left of `src` onto this image, with its top-left corner at `(x, y)`.
+    ///
+    /// Any part of it which goes off the edge of this image is left out.
+    pub fn paste(&mut self, src: &Image, x: isize, y: isize, width: usize, height: usize) {
+        for (j, src_row) in src.iter().enumerate().take(height) {
+            for (i, &value) in src_row.iter().enumerate().take(width) {
+                let (dest_x, dest_y) = (x.saturating_add(i as isize), y.saturating_add(j as isize));
+                if (0..5).contains(&dest_x) && (0..5).contains(&dest_y) {
+                    self[dest_y as usize][dest_x as usize] = value;
+                }
+            }
+        }
+    }
+
+    /// Adds the brightness of each LED in `other` to this image's, maxing out at 255.
+    pub fn blend_add(&self, other: &Image) -> Self {
+        Self::from_fn(|x, y| self[y][x].saturating_add(other[y][x]))
+    }
+
+    /// Takes whichever of this image and `other` is brighter for each LED.
+    pub fn blend_max(&self, other: &Image) -> Self {
+        Self::from_fn(|x, y| self[y][x].max(other[y][x]))
+    }
+
+    /// Scales the brightness of every LED, where 255 leaves them as they are and 0 turns them all off.
+    pub fn scale_brightness(&self, scale: u8) -> Self {
+        Self::from_fn(|x, y| (self[y][x] as u32 * scale as u32 / 255) as u8)
+    }
+}
+
+/// Converts a distance to shift by into an `isize`, clamping it so that huge values don't wrap around to negative ones.
+///
+/// Anything past 5 moves the whole image off the edge anyway.
+fn shift_distance(n: usize) -> isize {
+    n.min(5) as isize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An image where every LED has a different value, so that it's obvious if any of them end up in the wrong place.
+    fn numbered() -> Image {
+        Image::from_fn(|x, y| (y * 5 + x + 1) as u8)
+    }
+
+    #[test]
+    fn rotations() {
+        let image = numbered();
+        assert_ne!(image.rotate_90(), image);
+        assert_eq!(image.rotate_90().rotate_90(), image.rotate_180());
+        assert_eq!(
+            image.rotate_90().rotate_90().rotate_90(),
+            image.rotate_270()
+        );
+        assert_eq!(image.rotate_90().rotate_90().rotate_90().rotate_90(), image);
+        assert_eq!(image.rotate_270().rotate_90(), image);
+
+        // The top-left corner ends up in the top-right after a quarter turn clockwise.
+        assert_eq!(image.rotate_90()[0][4], image[0][0]);
+    }
+
+    #[test]
+    fn flips() {
+        let image = numbered();
+        assert_ne!(image.flip_horizontal(), image);
+        assert_ne!(image.flip_vertical(), image);
+        assert_eq!(image.flip_horizontal().flip_horizontal(), image);
+        assert_eq!(image.flip_vertical().flip_vertical(), image);
+        assert_eq!(image.flip_horizontal().flip_vertical(), image.rotate_180());
+    }
+
+    #[test]
+    fn shift() {
+        let image = numbered();
+
+        let shifted = image.shift(2, -1);
+        let wrapped = image.wrapping_shift(2, -1);
+        for y in 0..5 {
+            for x in 0..5 {
+                let (src_x, src_y) = (x as isize - 2, y as isize + 1);
+                let expected = if (0..5).contains(&src_x) && (0..5).contains(&src_y) {
+                    image[src_y as usize][src_x as usize]
+                } else {
+                    0
+                };
+                assert_eq!(shifted[y][x], expected, "shift at ({}, {})", x, y);
+                assert_eq!(
+                    wrapped[y][x],
+                    image[(y + 1) % 5][(x + 3) % 5],
+                    "wrapping_shift at ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+
+        assert_eq!(image.shift_right(2), image.shift(2, 0));
+        assert_eq!(image.shift_up(1), image.shift(0, -1));
+
+        // Shifting all the way across blanks the image, but wrapping all the way around leaves it as it was.
+        assert_eq!(image.shift(5, 0), Image::BLANK);
+        assert_eq!(image.shift(0, -5), Image::BLANK);
+        assert_eq!(image.wrapping_shift(5, -10), image);
+        assert_eq!(image.wrapping_shift(-3, 0), image.wrapping_shift(2, 0));
+    }
+
+    #[test]
+    fn shift_extremes() {
+        let image = numbered();
+        assert_eq!(image.shift(isize::MIN, isize::MAX), Image::BLANK);
+        assert_eq!(image.shift(isize::MAX, isize::MIN), Image::BLANK);
+        assert_eq!(image.shift_left(usize::MAX), Image::BLANK);
+        assert_eq!(image.shift_down(usize::MAX), Image::BLANK);
+        assert_eq!(
+            image.wrapping_shift(isize::MIN, isize::MAX),
+            image.wrapping_shift(isize::MIN.rem_euclid(5), isize::MAX.rem_euclid(5))
+        );
+    }
+
+    #[test]
+    fn crop() {
+        let image = numbered();
+
+        let cropped = image.crop(1, 2, 3, 2);
+        for y in 0..5 {
+            for x in 0..5 {
+                let expected = if x < 3 && y < 2 {
+                    image[y + 2][x + 1]
+                } else {
+                    0
+                };
+                assert_eq!(cropped[y][x], expected, "at ({}, {})", x, y);
+            }
+        }
+
+        // The part of the region that's off the edge is blank.
+        let cropped = image.crop(3, 3, 5, 5);
+        assert_eq!(cropped[1][1], image[4][4]);
+        assert_eq!(cropped[2][0], 0);
+        assert_eq!(cropped[0][2], 0);
+
+        assert_eq!(image.crop(0, 0, 5, 5), image);
+        assert_eq!(image.crop(0, 0, 0, 5), Image::BLANK);
+        assert_eq!(image.crop(5, 0, 5, 5), Image::BLANK);
+        assert_eq!(
+            image.crop(usize::MAX, usize::MAX, usize::MAX, usize::MAX),
+            Image::BLANK
+        );
+    }
+
+    #[test]
+    fn paste() {
+        let src = numbered();
+
+        let mut dest = Image::BLANK;
+        dest.paste(&src, 3, 1, 3, 2);
+        for y in 0..5 {
+            for x in 0..5 {
+                let expected = if x >= 3 && (1..3).contains(&y) {
+                    src[y - 1][x - 3]
+                } else {
+                    0
+                };
+                assert_eq!(dest[y][x], expected, "at ({}, {})", x, y);
+            }
+        }
+
+        // Pasting up and to the left of the image only keeps the bottom-right of `src`.
+        let mut dest = Image::BLANK;
+        dest.paste(&src, -3, -4, 5, 5);
+        assert_eq!(dest[0][0], src[4][3]);
+        assert_eq!(dest[0][1], src[4][4]);
+        assert_eq!(dest[0][2], 0);
+        assert_eq!(dest[1][0], 0);
+
+        // Pasting entirely off the edge leaves the image alone.
+        let mut dest = src.clone();
+        dest.paste(&Image::BLANK, isize::MAX, 0, 5, 5);
+        dest.paste(&Image::BLANK, 0, isize::MIN, 5, 5);
+        dest.paste(&Image::BLANK, -5, 0, 5, 5);
+        assert_eq!(dest, src);
+
+        // Pasting with the same size as the image replaces it.
+        let mut dest = Image::BLANK;
+        dest.paste(&src, 0, 0, usize::MAX, usize::MAX);
+        assert_eq!(dest, src);
+    }
+
+    #[test]
+    fn invert() {
+        let image = numbered();
+        let inverted = image.invert();
+        assert_eq!(inverted[0][0], 254);
+        assert_eq!(inverted[4][4], 230);
+        assert_eq!(inverted.invert(), image);
+        assert_eq!(Image::BLANK.invert(), Image([[255; 5]; 5]));
+    }
+
+    #[test]
+    fn blends() {
+        let image = numbered();
+        let bright = Image::from_fn(|x, _| 60 * x as u8);
+
+        let added = image.blend_add(&bright);
+        assert_eq!(added[0][0], 1);
+        assert_eq!(added[4][3], 204);
+        // 25 + 240 would overflow, so it saturates.
+        assert_eq!(added[4][4], 255);
+        assert_eq!(image.blend_add(&Image::BLANK), image);
+
+        let maxed = image.blend_max(&bright.invert());
+        assert_eq!(maxed[0][0], 255);
+        assert_eq!(maxed[4][4], 25);
+        assert_eq!(image.blend_max(&Image::BLANK), image);
+        assert_eq!(image.blend_max(&image), image);
+    }
+
+    #[test]
+    fn scale_brightness() {
+        let image = Image::from_fn(|x, y| (y * 5 + x) as u8 * 10);
+        assert_eq!(image.scale_brightness(255), image);
+        assert_eq!(image.scale_brightness(0), Image::BLANK);
+        assert_eq!(image.scale_brightness(128)[4][4], 120);
+        assert_eq!(
+            Image([[255; 5]; 5]).scale_brightness(255),
+            Image([[255; 5]; 5])
+        );
+    }
+}
@@ -0,0 +1,637 @@
+//! Turning debounced presses and releases into gestures like clicks, holds and chords, independent of any particular pin.
+
+// The button drivers which use this are only built for the micro:bit, so on the host it's only used by the tests.
+#![cfg_attr(not(target_os = "none"), allow(dead_code))]
+
+use defmt::Format;
+#[cfg(target_os = "none")]
+use embassy::time::Duration;
+#[cfg(target_os = "none")]
+use embassy::time::Instant;
+
+#[cfg(not(target_os = "none"))]
+use self::host_time::Duration;
+#[cfg(not(target_os = "none"))]
+use self::host_time::Instant;
+
+/// The number of events kept around for each button.
+///
+/// If a `Button` falls further behind than this, the oldest events are skipped.
+const EVENT_CAPACITY: usize = 8;
+
+/// A gesture performed with a button, modelled after the events emitted by the official micro:bit runtime.
+#[derive(Clone, Copy, Debug, Format, PartialEq, Eq)]
+pub enum ButtonEvent {
+    /// The button was pressed down.
+    Down,
+    /// The button was released.
+    Up,
+    /// The button was released before `ButtonConfig::long_click` had passed.
+    Click,
+    /// The button was released after being held for at least `ButtonConfig::long_click`.
+    LongClick,
+    /// The button was clicked twice within `ButtonConfig::double_click`.
+    ///
+    /// This is emitted straight after the second `Click`.
+    DoubleClick,
+    /// The button has been held for `ButtonConfig::hold`, and then every `ButtonConfig::hold_repeat` after that.
+    Hold,
+}
+
+/// The timings used to turn presses and releases into `ButtonEvent`s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ButtonConfig {
+    /// How long the button has to be held for releasing it to be a `LongClick` rather than a `Click`.
+    pub long_click: Duration,
+    /// The longest time between two clicks for them to count as a `DoubleClick`.
+    pub double_click: Duration,
+    /// How long the button has to be held for before a `Hold` event is emitted.
+    pub hold: Duration,
+    /// If set, `Hold` events keep being emitted at this interval for as long as the button is held.
+    pub hold_repeat: Option<Duration>,
+}
+
+impl ButtonConfig {
+    /// The same timings as the official micro:bit runtime.
+    pub const DEFAULT: Self = Self {
+        long_click: Duration::from_millis(1000),
+        double_click: Duration::from_millis(300),
+        hold: Duration::from_millis(1500),
+        hold_repeat: None,
+    };
+}
+
+impl Default for ButtonConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A ring buffer of the most recent events, which any number of readers can each keep their own position in.
+pub(crate) struct EventLog {
+    /// Each event, along with when it happened.
+    events: [(ButtonEvent, Instant); EVENT_CAPACITY],
+    /// The total number of events which have ever been pushed.
+    pub(crate) count: u32,
+}
+
+impl EventLog {
+    pub(crate) const fn new() -> Self {
+        Self {
+            events: [(ButtonEvent::Down, Instant::from_ticks(0)); EVENT_CAPACITY],
+            count: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, event: ButtonEvent, time: Instant) {
+        self.events[self.count as usize % EVENT_CAPACITY] = (event, time);
+        self.count = self.count.wrapping_add(1);
+    }
+
+    /// Returns the event after `cursor` and when it happened, if there is one, without advancing `cursor` past it.
+    pub(crate) fn peek(&self, cursor: &mut u32) -> Option<(ButtonEvent, Instant)> {
+        let behind = self.count.wrapping_sub(*cursor);
+        if behind == 0 {
+            return None;
+        }
+        if behind as usize > EVENT_CAPACITY {
+            // Those events have been overwritten, so skip to the oldest one we still have.
+            *cursor = self.count.wrapping_sub(EVENT_CAPACITY as u32);
+        }
+
+        Some(self.events[*cursor as usize % EVENT_CAPACITY])
+    }
+}
+
+/// Turns debounced presses and releases into `ButtonEvent`s.
+#[derive(Default)]
+pub(crate) struct Gestures {
+    /// When the button was pressed, if it's currently held.
+    pressed_at: Option<Instant>,
+    /// When the next `Hold` event is due.
+    next_hold: Option<Instant>,
+    /// When the last `Click` which hasn't been made into a `DoubleClick` yet happened.
+    last_click: Option<Instant>,
+}
+
+impl Gestures {
+    pub(crate) fn press(
+        &mut self,
+        now: Instant,
+        config: &ButtonConfig,
+        mut emit: impl FnMut(ButtonEvent),
+    ) {
+        emit(ButtonEvent::Down);
+        self.pressed_at = Some(now);
+        self.next_hold = Some(now + config.hold);
+    }
+
+    pub(crate) fn release(
+        &mut self,
+        now: Instant,
+        config: &ButtonConfig,
+        mut emit: impl FnMut(ButtonEvent),
+    ) {
+        emit(ButtonEvent::Up);
+        self.next_hold = None;
+
+        let pressed_at = match self.pressed_at.take() {
+            Some(pressed_at) => pressed_at,
+            None => return,
+        };
+
+        if now - pressed_at >= config.long_click {
+            emit(ButtonEvent::LongClick);
+            self.last_click = None;
+        } else {
+            emit(ButtonEvent::Click);
+            match self.last_click {
+                Some(last_click) if now - last_click <= config.double_click => {
+                    emit(ButtonEvent::DoubleClick);
+                    // Don't let a third click count as another double click.
+                    self.last_click = None;
+                }
+                _ => self.last_click = Some(now),
+            }
+        }
+    }
+
+    /// Returns when the next `Hold` event is due, if there is one.
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        self.next_hold
+    }
+
+    /// Emits any `Hold` events which have become due.
+    pub(crate) fn tick(
+        &mut self,
+        now: Instant,
+        config: &ButtonConfig,
+        mut emit: impl FnMut(ButtonEvent),
+    ) {
+        if let Some(next_hold) = self.next_hold {
+            if now >= next_hold {
+                emit(ButtonEvent::Hold);
+                self.next_hold = config.hold_repeat.map(|repeat| next_hold + repeat);
+            }
+        }
+    }
+}
+
+/// Which button an event returned by `Buttons` came from.
+#[derive(Clone, Copy, Debug, Format, PartialEq, Eq)]
+pub enum ButtonId {
+    A,
+    B,
+    /// Both buttons being held down together.
+    AB,
+}
+
+/// Keeps track of when both buttons are held down together, for `Buttons`.
+pub(crate) struct Chords {
+    a_held: bool,
+    b_held: bool,
+    /// Whether A's events are being suppressed because it's part of a chord.
+    a_chorded: bool,
+    /// Whether B's events are being suppressed because it's part of a chord.
+    b_chorded: bool,
+    /// When the current chord started, if there is one.
+    chord_start: Option<Instant>,
+}
+
+impl Chords {
+    /// Starts tracking chords, with the buttons currently held down as given.
+    ///
+    /// Their `Down`s were never reported, so they're suppressed until they're next pressed, the same as after a chord.
+    pub(crate) fn new(a_held: bool, b_held: bool) -> Self {
+        Self {
+            a_held,
+            b_held,
+            a_chorded: a_held,
+            b_chorded: b_held,
+            chord_start: None,
+        }
+    }
+
+    /// Updates the chord state with an event from one of the buttons which happened at `now`, and emits what should be reported for it.
+    ///
+    /// `config` decides whether a chord was long enough to be a `LongClick`.
+    pub(crate) fn process(
+        &mut self,
+        id: ButtonId,
+        event: ButtonEvent,
+        now: Instant,
+        config: &ButtonConfig,
+        mut emit: impl FnMut((ButtonId, ButtonEvent)),
+    ) {
+        let (held, chorded, other_id, other_held, other_chorded) = match id {
+            ButtonId::A => (
+                &mut self.a_held,
+                &mut self.a_chorded,
+                ButtonId::B,
+                self.b_held,
+                &mut self.b_chorded,
+            ),
+            ButtonId::B => (
+                &mut self.b_held,
+                &mut self.b_chorded,
+                ButtonId::A,
+                self.a_held,
+                &mut self.a_chorded,
+            ),
+            ButtonId::AB => unreachable!(),
+        };
+
+        match event {
+            ButtonEvent::Down => {
+                *held = true;
+                if self.chord_start.is_some() {
+                    // The chord is still going, since the other button hasn't been released yet; just rejoin it.
+                    *chorded = true;
+                } else if other_held {
+                    // The other button's `Down` has usually already been reported, so end it before starting the chord,
+                    // so that anything keeping track of which buttons are held doesn't think it still is.
+                    if !*other_chorded {
+                        emit((other_id, ButtonEvent::Up));
+                    }
+                    *chorded = true;
+                    *other_chorded = true;
+                    self.chord_start = Some(now);
+                    emit((ButtonId::AB, ButtonEvent::Down));
+                } else {
+                    *chorded = false;
+                    emit((id, event));
+                }
+            }
+            ButtonEvent::Up => {
+                *held = false;
+                if !*chorded {
+                    emit((id, event));
+                } else if !other_held {
+                    // This was the last button of the chord to be released, so the chord's over.
+                    // There won't be one if this button was being suppressed because it was held from the start.
+                    if let Some(start) = self.chord_start.take() {
+                        emit((ButtonId::AB, event));
+                        if now - start >= config.long_click {
+                            emit((ButtonId::AB, ButtonEvent::LongClick));
+                        } else {
+                            emit((ButtonId::AB, ButtonEvent::Click));
+                        }
+                    }
+                }
+            }
+            // The chord's clicks are worked out from how long the chord itself lasted, so the buttons' own are always suppressed.
+            ButtonEvent::Click
+            | ButtonEvent::LongClick
+            | ButtonEvent::DoubleClick
+            | ButtonEvent::Hold => {
+                if !*chorded {
+                    emit((id, event));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ButtonEvent::*;
+    use ButtonId::*;
+
+    fn at(millis: u64) -> Instant {
+        Instant::from_millis(millis)
+    }
+
+    /// Feeds each of a button's presses and releases into `gestures`, returning the events they produced.
+    fn gestures(config: &ButtonConfig, samples: &[(bool, u64)]) -> Vec<ButtonEvent> {
+        let mut gestures = Gestures::default();
+        let mut events = Vec::new();
+        for &(pressed, millis) in samples {
+            gestures.tick(at(millis), config, |event| events.push(event));
+            if pressed {
+                gestures.press(at(millis), config, |event| events.push(event));
+            } else {
+                gestures.release(at(millis), config, |event| events.push(event));
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn clicks() {
+        let config = ButtonConfig::DEFAULT;
+        assert_eq!(
+            gestures(&config, &[(true, 0), (false, 100)]),
+            [Down, Up, Click]
+        );
+        assert_eq!(
+            gestures(&config, &[(true, 0), (false, 1000)]),
+            [Down, Up, LongClick]
+        );
+        assert_eq!(
+            gestures(
+                &config,
+                &[(true, 0), (false, 100), (true, 200), (false, 300)]
+            ),
+            [Down, Up, Click, Down, Up, Click, DoubleClick]
+        );
+        // A third click straight after a double click doesn't make another one.
+        assert_eq!(
+            gestures(
+                &config,
+                &[
+                    (true, 0),
+                    (false, 50),
+                    (true, 100),
+                    (false, 150),
+                    (true, 200),
+                    (false, 250)
+                ]
+            ),
+            [
+                Down,
+                Up,
+                Click,
+                Down,
+                Up,
+                Click,
+                DoubleClick,
+                Down,
+                Up,
+                Click
+            ]
+        );
+        // Too slow to be a double click.
+        assert_eq!(
+            gestures(
+                &config,
+                &[(true, 0), (false, 100), (true, 500), (false, 600)]
+            ),
+            [Down, Up, Click, Down, Up, Click]
+        );
+    }
+
+    #[test]
+    fn holds() {
+        let config = ButtonConfig {
+            hold_repeat: Some(Duration::from_millis(500)),
+            ..ButtonConfig::DEFAULT
+        };
+        let mut gestures = Gestures::default();
+        let mut events = Vec::new();
+
+        gestures.press(at(0), &config, |event| events.push(event));
+        assert_eq!(gestures.next_deadline(), Some(at(1500)));
+        gestures.tick(at(1499), &config, |event| events.push(event));
+        gestures.tick(at(1500), &config, |event| events.push(event));
+        assert_eq!(gestures.next_deadline(), Some(at(2000)));
+        gestures.tick(at(2000), &config, |event| events.push(event));
+        gestures.release(at(2100), &config, |event| events.push(event));
+        assert_eq!(gestures.next_deadline(), None);
+
+        assert_eq!(events, [Down, Hold, Hold, Up, LongClick]);
+    }
+
+    #[test]
+    fn event_log() {
+        let mut log = EventLog::new();
+        let mut cursor = log.count;
+        assert_eq!(log.peek(&mut cursor), None);
+
+        log.push(Down, at(0));
+        assert_eq!(log.peek(&mut cursor), Some((Down, at(0))));
+        // Peeking doesn't move the cursor on.
+        assert_eq!(log.peek(&mut cursor), Some((Down, at(0))));
+        cursor += 1;
+        assert_eq!(log.peek(&mut cursor), None);
+
+        // Falling too far behind skips to the oldest event still in the log.
+        for i in 0..EVENT_CAPACITY as u64 + 3 {
+            log.push(Hold, at(i));
+        }
+        assert_eq!(log.peek(&mut cursor), Some((Hold, at(3))));
+        assert_eq!(log.count - cursor, EVENT_CAPACITY as u32);
+    }
+
+    /// Feeds each of the buttons' events into `chords`, returning what it reported.
+    fn chords(
+        chords: &mut Chords,
+        events: &[(ButtonId, ButtonEvent, u64)],
+    ) -> Vec<(ButtonId, ButtonEvent)> {
+        let mut out = Vec::new();
+        for &(id, event, millis) in events {
+            chords.process(id, event, at(millis), &ButtonConfig::DEFAULT, |event| {
+                out.push(event)
+            });
+        }
+        out
+    }
+
+    #[test]
+    fn single_buttons() {
+        let mut state = Chords::new(false, false);
+        assert_eq!(
+            chords(
+                &mut state,
+                &[
+                    (A, Down, 0),
+                    (A, Up, 100),
+                    (A, Click, 100),
+                    (B, Down, 200),
+                    (B, Hold, 1700)
+                ]
+            ),
+            [(A, Down), (A, Up), (A, Click), (B, Down), (B, Hold)]
+        );
+    }
+
+    #[test]
+    fn chord_start() {
+        let mut state = Chords::new(false, false);
+        // A's `Down` has already gone out by the time B is pressed, so it has to be ended before the chord starts.
+        assert_eq!(chords(&mut state, &[(A, Down, 0)]), [(A, Down)]);
+        assert_eq!(chords(&mut state, &[(B, Down, 50)]), [(A, Up), (AB, Down)]);
+    }
+
+    #[test]
+    fn chord_end() {
+        // Whichever button is released second ends the chord.
+        for (first, second) in [(A, B), (B, A)] {
+            let mut state = Chords::new(false, false);
+            chords(&mut state, &[(A, Down, 0), (B, Down, 50)]);
+
+            assert_eq!(
+                chords(&mut state, &[(first, Up, 200), (first, Click, 200)]),
+                []
+            );
+            assert_eq!(
+                chords(&mut state, &[(second, Up, 250), (second, Click, 250)]),
+                [(AB, Up), (AB, Click)]
+            );
+
+            // After that, the buttons are back to being reported on their own.
+            assert_eq!(
+                chords(
+                    &mut state,
+                    &[(first, Down, 400), (first, Up, 450), (first, Click, 450)]
+                ),
+                [(first, Down), (first, Up), (first, Click)]
+            );
+        }
+    }
+
+    #[test]
+    fn chord_duration() {
+        // A was held for long enough to long click on its own, but the chord itself was short.
+        let mut state = Chords::new(false, false);
+        assert_eq!(
+            chords(
+                &mut state,
+                &[
+                    (A, Down, 0),
+                    (A, Hold, 1500),
+                    (B, Down, 2000),
+                    (B, Up, 2100),
+                    (B, Click, 2100),
+                    (A, Up, 2200),
+                    (A, LongClick, 2200),
+                ]
+            ),
+            [
+                (A, Down),
+                (A, Hold),
+                (A, Up),
+                (AB, Down),
+                (AB, Up),
+                (AB, Click)
+            ]
+        );
+
+        // Both buttons were only clicked, but the chord went on for long enough to be a long click.
+        let mut state = Chords::new(false, false);
+        assert_eq!(
+            chords(
+                &mut state,
+                &[
+                    (A, Down, 0),
+                    (B, Down, 100),
+                    (A, Up, 900),
+                    (A, Click, 900),
+                    (A, Down, 950),
+                    (B, Up, 1000),
+                    (B, Click, 1000),
+                    (A, Up, 1100),
+                    (A, Click, 1100),
+                ]
+            ),
+            [(A, Down), (A, Up), (AB, Down), (AB, Up), (AB, LongClick)]
+        );
+    }
+
+    #[test]
+    fn chord_rejoin() {
+        let mut state = Chords::new(false, false);
+        chords(&mut state, &[(A, Down, 0), (B, Down, 50)]);
+
+        // Letting go of A and pressing it again while B's still held carries on the same chord.
+        assert_eq!(
+            chords(&mut state, &[(A, Up, 100), (A, Click, 100), (A, Down, 150)]),
+            []
+        );
+        assert_eq!(chords(&mut state, &[(B, Up, 200), (B, Click, 200)]), []);
+        assert_eq!(
+            chords(&mut state, &[(A, Up, 300), (A, Click, 300)]),
+            [(AB, Up), (AB, Click)]
+        );
+    }
+
+    #[test]
+    fn chord_suppression() {
+        let mut state = Chords::new(false, false);
+        chords(&mut state, &[(A, Down, 0), (B, Down, 50)]);
+
+        assert_eq!(
+            chords(
+                &mut state,
+                &[
+                    (A, Hold, 1500),
+                    (B, Hold, 1550),
+                    (A, Up, 1600),
+                    (A, LongClick, 1600),
+                    (A, Down, 1650),
+                    (A, Up, 1700),
+                    (A, Click, 1700),
+                    (A, DoubleClick, 1700),
+                ]
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn held_at_start() {
+        // B was already held when the buttons started being tracked, so its `Down` was never reported.
+        let mut state = Chords::new(false, true);
+        assert_eq!(chords(&mut state, &[(A, Down, 0)]), [(AB, Down)]);
+
+        // If it's released on its own, that isn't reported either.
+        let mut state = Chords::new(false, true);
+        assert_eq!(chords(&mut state, &[(B, Up, 0), (B, Click, 0)]), []);
+        assert_eq!(chords(&mut state, &[(B, Down, 100)]), [(B, Down)]);
+    }
+}
+
+/// A stand-in for the parts of `embassy::time` used here, since embassy isn't built on the host.
+///
+/// Ticks are milliseconds.
+#[cfg(not(target_os = "none"))]
+mod host_time {
+    use core::ops::Add;
+    use core::ops::Sub;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct Duration(u64);
+
+    impl Duration {
+        pub const fn from_millis(millis: u64) -> Self {
+            Self(millis)
+        }
+    }
+
+    impl Add for Duration {
+        type Output = Self;
+
+        fn add(self, rhs: Self) -> Self {
+            Self(self.0 + rhs.0)
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct Instant(u64);
+
+    impl Instant {
+        pub const fn from_ticks(ticks: u64) -> Self {
+            Self(ticks)
+        }
+
+        pub const fn from_millis(millis: u64) -> Self {
+            Self(millis)
+        }
+    }
+
+    impl Add<Duration> for Instant {
+        type Output = Self;
+
+        fn add(self, rhs: Duration) -> Self {
+            Self(self.0 + rhs.0)
+        }
+    }
+
+    impl Sub for Instant {
+        type Output = Duration;
+
+        fn sub(self, rhs: Self) -> Duration {
+            Duration(self.0 - rhs.0)
+        }
+    }
+}
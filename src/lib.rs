@@ -1,17 +1,47 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 #![feature(type_alias_impl_trait)]
 
+// Only the hardware-independent parts of the crate are built on the host, for unit testing.
+#[cfg(target_os = "none")]
 pub mod button;
+pub mod debounce;
 pub mod display;
+mod gesture;
+#[cfg(target_os = "none")]
 pub mod pins;
+#[cfg(all(v2, target_os = "none"))]
+pub mod touch;
 
+#[cfg(target_os = "none")]
 pub use button::Button;
+#[cfg(target_os = "none")]
 pub use display::Display;
 
+/// Parses an `Image` from a MicroPython-style string like `"09090:99999:99999:09990:00900"` at compile time.
+///
+/// See `Image::parse` for the exact format.
+#[macro_export]
+macro_rules! image {
+    ($s:expr) => {{
+        const IMAGE: $crate::display::Image = match $crate::display::Image::parse($s) {
+            ::core::result::Result::Ok(image) => image,
+            ::core::result::Result::Err(_) => ::core::panic!("invalid image string"),
+        };
+        IMAGE
+    }};
+}
+
 #[cfg(not(v2))]
 #[macro_export]
 macro_rules! display {
-    ($peripherals:ident) => {{
+    ($peripherals:ident) => {
+        $crate::display!(
+            $peripherals,
+            TIMER1,
+            $crate::display::DisplayConfig::DEFAULT
+        )
+    };
+    ($peripherals:ident, $timer:ident, $config:expr) => {{
         use ::embassy_nrf::interrupt;
 
         let pins = $crate::display::Pins {
@@ -29,14 +59,21 @@ macro_rules! display {
             col9: $peripherals.P0_12,
         };
 
-        $crate::Display::new(pins, $peripherals.TIMER1, interrupt::take!(TIMER1))
+        $crate::Display::with_config(pins, $peripherals.$timer, interrupt::take!($timer), $config)
     }};
 }
 
 #[cfg(v2)]
 #[macro_export]
 macro_rules! display {
-    ($peripherals:ident) => {{
+    ($peripherals:ident) => {
+        $crate::display!(
+            $peripherals,
+            TIMER1,
+            $crate::display::DisplayConfig::DEFAULT
+        )
+    };
+    ($peripherals:ident, $timer:ident, $config:expr) => {{
         use ::embassy_nrf::interrupt;
 
         let pins = $crate::display::Pins {
@@ -52,7 +89,7 @@ macro_rules! display {
             col5: $peripherals.P0_30,
         };
 
-        $crate::Display::new(pins, $peripherals.TIMER1, interrupt::take!(TIMER1))
+        $crate::Display::with_config(pins, $peripherals.$timer, interrupt::take!($timer), $config)
     }};
 }
 
@@ -1,3 +1,5 @@
+use embassy_nrf::pac;
+
 #[cfg(not(v2))]
 mod v1;
 #[cfg(not(v2))]
@@ -7,3 +9,21 @@ pub use v1::*;
 mod v2;
 #[cfg(v2)]
 pub use v2::*;
+
+#[cfg(not(v2))]
+pub(crate) type GpioRegs = pac::gpio::RegisterBlock;
+#[cfg(v2)]
+pub(crate) type GpioRegs = pac::p0::RegisterBlock;
+
+/// Returns the GPIO port a pin is on and its number within that port.
+// TODO: Make a proper binding for this.
+pub(crate) fn gpio_port(psel: u32) -> (&'static GpioRegs, usize) {
+    #[cfg(not(v2))]
+    return (unsafe { &*pac::GPIO::ptr() }, psel as usize);
+    #[cfg(v2)]
+    if psel >= 32 {
+        (unsafe { &*pac::P1::ptr() }, psel as usize - 32)
+    } else {
+        (unsafe { &*pac::P0::ptr() }, psel as usize)
+    }
+}
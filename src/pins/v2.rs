@@ -24,6 +24,7 @@ use embassy_nrf::peripherals::P0_30;
 use embassy_nrf::peripherals::P0_31;
 use embassy_nrf::peripherals::P1_00;
 use embassy_nrf::peripherals::P1_02;
+use embassy_nrf::peripherals::P1_04;
 use embassy_nrf::peripherals::P1_05;
 use embassy_nrf::peripherals::P1_08;
 
@@ -74,5 +75,7 @@ pin!(pin16, Pin16 = P1_02);
 pin!(pin19, Pin19 = P0_26);
 pin!(pin20, Pin20 = P1_00);
 
+pin!(logo, Logo = P1_04);
+
 pin!(uart_rx, UartRx = P1_08);
 pin!(uart_tx, UartTx = P0_06);
@@ -0,0 +1,264 @@
+//! Capacitive touch sensing for the micro:bit v2's logo and the large pins of the edge connector.
+
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::ops::DerefMut;
+use core::sync::atomic::Ordering;
+
+use cortex_m::peripheral::DCB;
+use cortex_m::peripheral::DWT;
+use embassy::executor::Spawner;
+use embassy::task;
+use embassy::time::Duration;
+use embassy::time::Instant;
+use embassy::time::Timer;
+use embassy::util::Steal;
+use embassy_nrf::gpio;
+use embassy_nrf::gpio::AnyPin;
+use embassy_nrf::gpio::Level;
+use embassy_nrf::gpio::OutputDrive;
+use embassy_nrf::gpio::Pin;
+use embassy_nrf::gpio::Pull;
+use embedded_hal::digital::v2::InputPin;
+use futures::future::select;
+use futures::future::Either;
+use futures::pin_mut;
+
+use crate::button::ButtonState;
+use crate::button::Subscriber;
+use crate::debounce::Debouncer;
+use crate::gesture::Gestures;
+use crate::pins::Logo;
+use crate::pins::Pin0;
+use crate::pins::Pin1;
+use crate::pins::Pin2;
+
+/// How often each touch input is sampled.
+const SAMPLE_PERIOD: Duration = Duration::from_millis(5);
+/// The number of samples averaged together to find a touch input's untouched charge time.
+const CALIBRATION_SAMPLES: u32 = 16;
+/// How long to wait before trying again when a pin can't be calibrated.
+const CALIBRATION_RETRY_PERIOD: Duration = Duration::from_secs(1);
+/// The longest charge time we'll wait for, in microseconds, so that a pin which never charges doesn't hang the task.
+const MAX_CHARGE_TIME: u32 = 2000;
+/// If the pin goes longer than this without being checked, in microseconds, something interrupted the measurement.
+const MAX_POLL_GAP: u32 = 2;
+/// The number of times to retry a measurement which keeps getting interrupted before giving up on it.
+const MAX_ATTEMPTS: u32 = 8;
+/// The nRF52833's CPU runs at 64MHz.
+const CYCLES_PER_MICRO: u32 = 64;
+
+/// A pin which is wired up for capacitive touch sensing.
+///
+/// These all have a large external pull-up resistor, which is what lets us measure their capacitance.
+pub trait TouchCapable: Pin + sealed::Sealed {}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! touch_capable {
+    ($($pin:ty),*) => {
+        $(
+            impl sealed::Sealed for $pin {}
+            impl TouchCapable for $pin {}
+        )*
+    };
+}
+
+touch_capable!(Logo, Pin0, Pin1, Pin2);
+
+/// Turns on the CPU's cycle counter, which is used to time how long pins take to charge.
+fn enable_cycle_counter() {
+    const DEMCR_TRCENA: u32 = 1 << 24;
+    const DWT_CTRL_CYCCNTENA: u32 = 1 << 0;
+
+    // TODO: Make a proper binding for this.
+    unsafe {
+        (*DCB::ptr()).demcr.modify(|demcr| demcr | DEMCR_TRCENA);
+        (*DWT::ptr()).ctrl.modify(|ctrl| ctrl | DWT_CTRL_CYCCNTENA);
+    }
+}
+
+/// Measures how long `pin` takes to charge back up through its pull-up resistor after being discharged, in microseconds.
+///
+/// Touching the pin adds capacitance, which makes it take longer.
+/// This is timed with the cycle counter without disabling interrupts, so that it doesn't hold up the display;
+/// instead, if an interrupt delays checking the pin, that measurement is thrown away and taken again.
+/// Returns `None` if that keeps happening.
+fn charge_time(pin: &mut AnyPin) -> Option<u32> {
+    'attempts: for _ in 0..MAX_ATTEMPTS {
+        {
+            let _discharge = gpio::Output::new(&mut *pin, Level::Low, OutputDrive::Standard);
+            // The pad is tiny, so a few cycles is plenty to discharge it.
+            for _ in 0..16 {
+                core::hint::spin_loop();
+            }
+        }
+
+        let input = gpio::Input::new(&mut *pin, Pull::None);
+        let start = DWT::cycle_count();
+        let mut last_poll = start;
+        loop {
+            let charged = input.is_high().unwrap();
+            let now = DWT::cycle_count();
+            if now.wrapping_sub(last_poll) > MAX_POLL_GAP * CYCLES_PER_MICRO {
+                continue 'attempts;
+            }
+            last_poll = now;
+
+            let time = now.wrapping_sub(start) / CYCLES_PER_MICRO;
+            if charged {
+                return Some(time);
+            }
+            if time >= MAX_CHARGE_TIME {
+                return Some(MAX_CHARGE_TIME);
+            }
+        }
+    }
+
+    None
+}
+
+/// Works out the charge time of `pin` while nobody's touching it.
+///
+/// Returns `None` if the pin never charges, which means it won't be able to detect touches.
+async fn calibrate(pin: &mut AnyPin) -> Option<u32> {
+    let mut total = 0;
+    let mut samples = 0;
+    for _ in 0..CALIBRATION_SAMPLES {
+        if let Some(time) = charge_time(pin) {
+            total += time;
+            samples += 1;
+        }
+        Timer::after(Duration::from_millis(1)).await;
+    }
+
+    if samples == 0 {
+        defmt::warn!("touch pin calibration kept getting interrupted");
+        return None;
+    }
+
+    let baseline = total / samples;
+    if baseline >= MAX_CHARGE_TIME {
+        defmt::warn!("touch pin never charged while calibrating; it may be shorted to ground");
+        None
+    } else {
+        Some(baseline)
+    }
+}
+
+// This has to be a literal; there are only 4 touch-capable pins.
+#[task(pool_size = 4)]
+async fn watch_touch(mut pin: AnyPin, state: &'static ButtonState) {
+    let mut debouncer = Debouncer::new();
+    let mut gestures = Gestures::default();
+
+    enable_cycle_counter();
+
+    let mut baseline = calibrate(&mut pin).await;
+    let mut next_sample = Instant::now();
+
+    loop {
+        if state.recalibrate.swap(false, Ordering::Relaxed) {
+            baseline = calibrate(&mut pin).await;
+        }
+
+        match baseline {
+            Some(baseline) => {
+                // If the measurement failed, just skip this sample.
+                if let Some(time) = charge_time(&mut pin) {
+                    // Count it as a touch once the pin takes at least 50% longer to charge than normal.
+                    let touched = time > baseline + baseline / 2 + 1;
+                    let now = Instant::now();
+
+                    critical_section::with(|cs| {
+                        state.update(cs, touched, &mut debouncer, &mut gestures, now)
+                    });
+                }
+
+                next_sample += SAMPLE_PERIOD;
+            }
+            None => {
+                // Keep trying, in case whatever was stopping it from charging goes away.
+                baseline = calibrate(&mut pin).await;
+                next_sample = Instant::now() + CALIBRATION_RETRY_PERIOD;
+            }
+        }
+
+        let timer = Timer::at(next_sample);
+        let stop = state.wait_for_stop();
+        pin_mut!(stop);
+
+        if let Either::Right(_) = select(timer, stop).await {
+            break;
+        }
+    }
+
+    // Make sure the pin's been disconnected before `TouchPin::free` hands it back out.
+    drop(pin);
+    state.release();
+}
+
+/// A capacitive touch input, which behaves exactly like a `Button`.
+///
+/// The pin is calibrated when this is created, so it shouldn't be touched at that point.
+pub struct TouchPin<T: TouchCapable> {
+    subscriber: Subscriber,
+    _pin: PhantomData<T>,
+}
+
+impl<T: TouchCapable> TouchPin<T> {
+    /// # Panics
+    ///
+    /// Panics if there are already `button::MAX_INPUTS` debounced inputs.
+    pub fn new(pin: T, spawner: &Spawner) -> Self {
+        let state = ButtonState::claim().expect("too many debounced inputs");
+        spawner.spawn(watch_touch(pin.degrade(), state)).unwrap();
+
+        Self {
+            subscriber: Subscriber::new(state),
+            _pin: PhantomData,
+        }
+    }
+
+    /// Re-measures the charge time of the pin while it isn't being touched, in case conditions have changed.
+    pub fn calibrate(&self) {
+        self.subscriber
+            .state()
+            .recalibrate
+            .store(true, Ordering::Relaxed);
+    }
+
+    /// Returns a new handle for observing the input, which starts out having seen everything that's happened so far.
+    pub fn subscribe(&self) -> Subscriber {
+        Subscriber::new(self.subscriber.state())
+    }
+
+    /// Stops sensing touches and returns the pin.
+    ///
+    /// Any remaining subscribers stop receiving anything; waiting on one of them will never complete.
+    pub async fn free(self) -> T
+    where
+        T: Steal,
+    {
+        self.subscriber.stop().await;
+
+        // Safety: the pin was moved into the sensing task, which has now dropped it.
+        unsafe { T::steal() }
+    }
+}
+
+impl<T: TouchCapable> Deref for TouchPin<T> {
+    type Target = Subscriber;
+
+    fn deref(&self) -> &Self::Target {
+        &self.subscriber
+    }
+}
+
+impl<T: TouchCapable> DerefMut for TouchPin<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.subscriber
+    }
+}